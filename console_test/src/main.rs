@@ -5,9 +5,7 @@ extern crate tetris;
 
 use tetris::*;
 
-use std::cell::RefCell;
 use std::io::{stdin, stdout, Write};
-use std::rc::Rc;
 use std::sync::mpsc::{channel, Receiver, RecvError, Sender};
 use std::sync::{Arc, RwLock};
 use std::thread::Thread;
@@ -27,6 +25,8 @@ pub struct PressedKeyRegistry {
     pub down: bool,
     pub rotate_l: bool,
     pub rotate_r: bool,
+    pub rotate_180: bool,
+    pub hold: bool,
     pub space: bool,
 }
 
@@ -39,6 +39,8 @@ impl PressedKeyRegistry {
             down,
             rotate_l,
             rotate_r,
+            rotate_180,
+            hold,
             ..
         } = self;
 
@@ -65,6 +67,14 @@ impl PressedKeyRegistry {
         if rotate_r {
             game_step(Event::RotateR);
         }
+
+        if rotate_180 {
+            game_step(Event::Rotate180);
+        }
+
+        if hold {
+            game_step(Event::Hold);
+        }
     }
 }
 
@@ -92,6 +102,8 @@ fn spawn_stdin_listener() -> (RegistryRef, Receiver<()>) {
                 Ok(event::Key::Up) => inner_ref.write().unwrap().fall = true,
                 Ok(event::Key::Char('a')) => inner_ref.write().unwrap().rotate_l = true,
                 Ok(event::Key::Char('s')) => inner_ref.write().unwrap().rotate_r = true,
+                Ok(event::Key::Char('w')) => inner_ref.write().unwrap().rotate_180 = true,
+                Ok(event::Key::Char('d')) => inner_ref.write().unwrap().hold = true,
                 Ok(event::Key::Char(' ')) => inner_ref.write().unwrap().space = true,
                 Ok(event::Key::Ctrl('c')) => {
                     drop(sender);
@@ -110,22 +122,7 @@ fn spawn_game_thread(reg_ref: RegistryRef) {
 
     thread::spawn(move || {
         let mut stdout = stdout();
-        let mut alive = Rc::new(RefCell::new(false));
-
-        let mut game = {
-            let alive = alive.clone();
-            Game::new([123; 16], move |event| match event {
-                GameEvent::ChangeNextMinoAggregation => {}
-                GameEvent::Start => *alive.as_ref().borrow_mut() = true,
-                GameEvent::Overflow => *alive.as_ref().borrow_mut() = false,
-                GameEvent::ScoreChange(score) => print_scores(score),
-                GameEvent::Next(next_list) => print_next_minos(next_list),
-                GameEvent::Nop => {}
-            })
-        };
-
-        let game_step_interval = 10;
-        let mut game_step_interval_now = 0;
+        let mut game = Game::with_queue([123; 16], GameConfig::default()).unwrap();
 
         let consume_registry = || {
             let mut reg = Default::default();
@@ -153,7 +150,9 @@ fn spawn_game_thread(reg_ref: RegistryRef) {
             game.start();
 
             'game: loop {
-                if !*alive.borrow() {
+                game.drain_events().for_each(print_event);
+
+                if !game.is_alive() {
                     break 'game;
                 }
 
@@ -161,12 +160,7 @@ fn spawn_game_thread(reg_ref: RegistryRef) {
 
                 reg.call_step_with_event(|e| game.step(e));
 
-                game_step_interval_now += 1;
-
-                if game_step_interval_now > game_step_interval {
-                    game.step(Event::TimeGo);
-                    game_step_interval_now = 0;
-                }
+                game.step(Event::TimeGo);
 
                 write!(stdout, "{}{}", Goto(1, 1), print_field(&game)).unwrap();
                 thread::sleep(wait);
@@ -180,6 +174,15 @@ fn spawn_game_thread(reg_ref: RegistryRef) {
     });
 }
 
+fn print_event(event: GameEvent) {
+    match event {
+        GameEvent::ScoreChange(score) => print_scores(score),
+        GameEvent::Next(next_list) => print_next_minos(&next_list),
+        GameEvent::Hold(kind) => print_hold_mino(kind),
+        _ => {}
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (registry, receiver) = spawn_stdin_listener();
 
@@ -206,19 +209,41 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 // ⬜: mino
-// ⬛: locked
+// ⬛: locked (colored by mino)
+// ・: ghost
 // 　: blank
-fn print_field<F: FnMut(GameEvent)>(game: &Game<F>) -> String {
+fn print_field(game: &Game) -> String {
+    let locked = [
+        MinoKind::I,
+        MinoKind::O,
+        MinoKind::S,
+        MinoKind::Z,
+        MinoKind::J,
+        MinoKind::L,
+        MinoKind::T,
+    ]
+    .iter()
+    .map(|kind| colored_block(Block::Mino(*kind)))
+    .collect::<Vec<_>>();
+    let garbage = colored_block(Block::Garbage);
+
     // field + bottom line
-    let mut minos = [["　"; FIELD_W + 1]; FIELD_H + 1];
+    let field = game.field();
+    let mut minos = vec![vec!["　"; field.width() + 1]; field.height() + 1];
 
-    mut_with_absolute_cells(game.mino(), |x, y| minos[y as usize][x as usize] = "⬜");
+    game.ghost_cells()
+        .iter()
+        .for_each(|(x, y)| minos[*y as usize][*x as usize] = "・");
+    Piece::from(*game.mino())
+        .cells()
+        .iter()
+        .for_each(|(x, y)| minos[*y as usize][*x as usize] = "⬜");
 
-    game.rows().iter().enumerate().rev().for_each(|(y, row)| {
-        row.iter().enumerate().for_each(|(x, cell)| {
-            if *cell {
-                minos[y][x] = "⬛";
-            }
+    game.rows().enumerate().rev().for_each(|(y, row)| {
+        row.iter().enumerate().for_each(|(x, cell)| match cell {
+            Block::Empty => {}
+            Block::Mino(kind) => minos[y][x] = &locked[kind.index()],
+            Block::Garbage => minos[y][x] = &garbage,
         });
     });
     minos.last_mut().unwrap().iter_mut().for_each(|c| *c = "──");
@@ -227,11 +252,25 @@ fn print_field<F: FnMut(GameEvent)>(game: &Game<F>) -> String {
         .for_each(|mut row| *row.last_mut().unwrap() = "|\n\r");
     *minos.last_mut().unwrap().last_mut().unwrap() = "┘\n\r";
 
-    minos[FIELD_TOP as usize..]
+    minos[game.config().field_top() as usize..]
         .iter_mut()
         .fold(String::new(), |mut a, row| row.iter().fold(a, |a, s| a + s))
 }
 
+fn colored_block(block: Block) -> String {
+    let color: &dyn color::Color = match block {
+        Block::Mino(MinoKind::I) => &color::Cyan,
+        Block::Mino(MinoKind::O) => &color::Yellow,
+        Block::Mino(MinoKind::S) => &color::Green,
+        Block::Mino(MinoKind::Z) => &color::Red,
+        Block::Mino(MinoKind::J) => &color::Blue,
+        Block::Mino(MinoKind::L) => &color::LightRed,
+        Block::Mino(MinoKind::T) => &color::Magenta,
+        Block::Garbage | Block::Empty => &color::LightBlack,
+    };
+    format!("{}⬛{}", color::Fg(color), color::Fg(color::Reset))
+}
+
 fn print_press_enter() {
     let mut stdout = stdout();
     "\
@@ -260,12 +299,12 @@ fn clear_press_enter() {
     });
 }
 
-fn print_next_minos(next_list: &[usize]) {
+fn print_next_minos(next_list: &[MinoKind]) {
     let mut stdout = stdout();
     next_list
         .iter()
         .flat_map(|i| {
-            let now = &MINOS_SRC_ZERO_POSITION[*i];
+            let now = &MINOS_SRC_ZERO_POSITION[i.index()];
             let mino = print_next(now);
             mino.split("\n").map(String::from).collect::<Vec<String>>()
         })
@@ -275,21 +314,51 @@ fn print_next_minos(next_list: &[usize]) {
         });
 }
 
+fn print_hold_mino(kind: MinoKind) {
+    let mut stdout = stdout();
+    print_next(&MINOS_SRC_ZERO_POSITION[kind.index()])
+        .split("\n")
+        .enumerate()
+        .for_each(|(i, mino_row)| {
+            write!(stdout, "{}{}\n", Goto(34, (i + 2) as u16), mino_row).unwrap();
+        });
+}
+
 fn print_scores(score: Score) {
     let mut stdout = stdout();
     let Score {
+        level,
+        point,
+        combo,
+        back_to_back,
+        perfect_clear,
         deleted_line,
+        t_spin_zero,
         t_spin1,
         t_spin2,
         t_spin3,
+        t_spin_mini_zero,
+        t_spin_mini1,
+        t_spin_mini2,
         tetris,
+        finesse_fault,
     } = score;
     [
+        (" level", level),
+        (" point", point),
+        (" combo", combo),
+        ("   b2b", back_to_back),
+        ("    pc", perfect_clear),
         ("  line", deleted_line),
+        ("spin 0", t_spin_zero),
         ("spin 1", t_spin1),
         ("spin 2", t_spin2),
         ("spin 3", t_spin3),
+        ("mini 0", t_spin_mini_zero),
+        ("mini 1", t_spin_mini1),
+        ("mini 2", t_spin_mini2),
         ("tetris", tetris),
+        ("faults", finesse_fault),
     ]
     .iter()
     .rev()
@@ -298,7 +367,7 @@ fn print_scores(score: Score) {
         write!(
             stdout,
             "{}{}: {}\n",
-            Goto(23, (20 - i) as u16),
+            Goto(34, (20 - i) as u16),
             label,
             score
         )
@@ -308,16 +377,12 @@ fn print_scores(score: Score) {
 
 fn print_next(mino: &MinoAggregation) -> String {
     let mut minos = [["　"; 4]; 4];
-    mut_with_absolute_cells(mino, |x, y| minos[y as usize][x as usize] = "⬜");
+    Piece::from(*mino)
+        .cells()
+        .iter()
+        .for_each(|(x, y)| minos[*y as usize][*x as usize] = "⬜");
 
     minos.iter_mut().fold(String::new(), |mut a, row| {
         row.iter().fold(a, |a, s| a + s) + "\n"
     })
 }
-
-pub fn mut_with_absolute_cells<F>(mino: &MinoAggregation, f: F)
-where
-    F: FnMut(i8, i8),
-{
-    define_macro_state_method!(mino, mut_with_absolute_cells(f));
-}
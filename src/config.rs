@@ -0,0 +1,115 @@
+use crate::*;
+
+/// Rules to put a new mino into the field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SpawnRule {
+    /// Game over when a new mino overlaps locked blocks.
+    pub block_out: bool,
+    /// Game over when a mino is locked entirely above the display.
+    pub lock_out: bool,
+    /// Move a new mino down a row at once if possible.
+    pub drop_on_spawn: bool,
+}
+
+impl Default for SpawnRule {
+    fn default() -> Self {
+        Self {
+            block_out: false,
+            lock_out: true,
+            drop_on_spawn: false,
+        }
+    }
+}
+
+/// Why GameConfig::validate refuses a config.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ConfigError {
+    // field_w out of MIN_FIELD_W to MAX_FIELD_W
+    FieldWidth,
+    // field_h over MAX_FIELD_H, or display_field_h over field_h
+    FieldHeight,
+    // preview over MAX_PREVIEW
+    Preview,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GameConfig {
+    /// Up to MAX_FIELD_W.
+    pub field_w: usize,
+    /// Rows at the bottom of the field. Up to field_h.
+    pub display_field_h: usize,
+    /// Up to MAX_FIELD_H. Rows over the display are for rotation and starting.
+    pub field_h: usize,
+
+    pub mino_first_position: (i8, i8),
+
+    /// Count of next minos informed by GameEvent::Next. Up to MAX_PREVIEW.
+    pub preview: usize,
+    pub randomizer: RandomizerRule,
+
+    pub rotation: RotationRule,
+
+    pub lock_delay: LockDelay,
+    /// Steps from locking to appearing of a next mino (ARE).
+    pub line_clear_delay: u8,
+
+    pub spawn: SpawnRule,
+
+    pub mode: GameMode,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::with_field_size(FIELD_W, DISPLAY_FIELD_H)
+    }
+}
+
+impl GameConfig {
+    /// Rows for rotation and starting are added over the display,
+    /// and minos spawn at the center of the width.
+    /// w must be from MIN_FIELD_W to MAX_FIELD_W, and the rows must not exceed MAX_FIELD_H,
+    /// or Game::new refuses it.
+    pub fn with_field_size(w: usize, display_h: usize) -> Self {
+        let hidden = FIELD_H - DISPLAY_FIELD_H;
+
+        Self {
+            field_w: w,
+            display_field_h: display_h,
+            field_h: display_h + hidden,
+
+            mino_first_position: ((w as i8 - 1) / 2, MINO_FIRST_POSITION.1),
+
+            preview: 3,
+            randomizer: RandomizerRule::default(),
+
+            rotation: RotationRule::default(),
+
+            lock_delay: LockDelay::default(),
+            line_clear_delay: LOCKING_WAIT_TIME,
+
+            spawn: SpawnRule::default(),
+
+            mode: GameMode::default(),
+        }
+    }
+
+    /// Check the limits of the fields, which Game::new and replays need.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if !(MIN_FIELD_W..=MAX_FIELD_W).contains(&self.field_w) {
+            return Err(ConfigError::FieldWidth);
+        }
+        if MAX_FIELD_H < self.field_h || self.field_h < self.display_field_h {
+            return Err(ConfigError::FieldHeight);
+        }
+        if MAX_PREVIEW < self.preview {
+            return Err(ConfigError::Preview);
+        }
+
+        Ok(())
+    }
+
+    /// The first row of the display.
+    pub fn field_top(&self) -> i8 {
+        (self.field_h - self.display_field_h) as i8
+    }
+}
@@ -0,0 +1,119 @@
+use crate::*;
+
+// Enough for the events of a step. 20G informs a move for each row up to MAX_FIELD_H,
+// and the other events of a step are a few.
+pub const EVENT_QUEUE_CAPACITY: usize = 64;
+
+/// Receiver of GameEvent.
+/// Any FnMut(GameEvent) is a sink, as well as EventQueue.
+pub trait EventSink {
+    fn receive(&mut self, event: GameEvent);
+}
+
+impl<F: FnMut(GameEvent)> EventSink for F {
+    fn receive(&mut self, event: GameEvent) {
+        self(event)
+    }
+}
+
+const NO_EVENT: Option<GameEvent> = None;
+
+/// Buffers events to be polled after each step.
+/// The oldest event is dropped when it is full, and counted in dropped.
+#[derive(Debug, Clone)]
+pub struct EventQueue {
+    events: [Option<GameEvent>; EVENT_QUEUE_CAPACITY],
+    head: usize,
+    len: usize,
+    // Count of events dropped since created
+    dropped: usize,
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self {
+            events: [NO_EVENT; EVENT_QUEUE_CAPACITY],
+            head: 0,
+            len: 0,
+            dropped: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn push(&mut self, event: GameEvent) {
+        if self.len == EVENT_QUEUE_CAPACITY {
+            self.pop();
+            self.dropped += 1;
+        }
+
+        let tail = (self.head + self.len) % EVENT_QUEUE_CAPACITY;
+        self.events[tail] = Some(event);
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<GameEvent> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let event = self.events[self.head].take();
+        self.head = (self.head + 1) % EVENT_QUEUE_CAPACITY;
+        self.len -= 1;
+        event
+    }
+
+    /// Pop all events in order.
+    pub fn drain(&mut self) -> impl Iterator<Item = GameEvent> + '_ {
+        core::iter::from_fn(move || self.pop())
+    }
+}
+
+impl EventSink for EventQueue {
+    fn receive(&mut self, event: GameEvent) {
+        self.push(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn test_event_queue() {
+        let mut queue = EventQueue::new();
+        for i in 0..EVENT_QUEUE_CAPACITY + 2 {
+            queue.push(GameEvent::ComboChanged(i));
+        }
+        assert_eq!(EVENT_QUEUE_CAPACITY, queue.len());
+        assert_eq!(2, queue.dropped());
+
+        let combos = queue
+            .drain()
+            .map(|e| match e {
+                GameEvent::ComboChanged(combo) => combo,
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!((2..EVENT_QUEUE_CAPACITY + 2).collect::<Vec<_>>(), combos);
+        assert!(queue.is_empty());
+        assert!(queue.pop().is_none());
+    }
+}
@@ -1,10 +1,124 @@
 use crate::*;
 use core::mem;
 
-#[derive(Default, Debug)]
+/// What fills a cell.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Block {
+    Empty,
+    Mino(MinoKind),
+    Garbage,
+}
+
+impl Default for Block {
+    fn default() -> Self {
+        Block::Empty
+    }
+}
+
+impl Block {
+    pub fn is_filled(&self) -> bool {
+        *self != Block::Empty
+    }
+}
+
+/// Bits of a row. A set bit is a filled cell or a wall.
+pub type RowBits = u32;
+
+// Columns start from this bit and bits out of the columns are walls,
+// so a mino beside the field hits them as well as blocks.
+const WALL_W: i8 = 8;
+const FILLED_ROW: RowBits = !0;
+
+/// Set of row indices, as a bit for each row.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ClearedRows(u64);
+
+impl ClearedRows {
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, y: i8) -> bool {
+        0 <= y && (y as usize) < MAX_FIELD_H && self.0 & 1 << y != 0
+    }
+
+    /// Indices from the top.
+    pub fn iter(&self) -> impl Iterator<Item = i8> {
+        let rows = *self;
+        (0..MAX_FIELD_H as i8).filter(move |y| rows.contains(*y))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SavedField", try_from = "SavedField")
+)]
 pub struct Field {
-    counts: [usize; FIELD_H],
-    rows: [[bool; FIELD_W]; FIELD_H],
+    w: usize,
+    h: usize,
+    // empty rows have only walls
+    walls: RowBits,
+    bits: [RowBits; MAX_FIELD_H],
+    // kinds of filled cells for rendering
+    rows: [[Block; MAX_FIELD_W]; MAX_FIELD_H],
+}
+
+/// Field saved without the bits, which are rebuilt from the blocks on load.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedField {
+    w: usize,
+    h: usize,
+    #[serde(with = "crate::big_array")]
+    rows: [[Block; MAX_FIELD_W]; MAX_FIELD_H],
+}
+
+#[cfg(feature = "serde")]
+impl From<Field> for SavedField {
+    fn from(field: Field) -> Self {
+        Self {
+            w: field.w,
+            h: field.h,
+            rows: field.rows,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl core::convert::TryFrom<SavedField> for Field {
+    type Error = &'static str;
+
+    fn try_from(saved: SavedField) -> Result<Self, Self::Error> {
+        if saved.w > MAX_FIELD_W || saved.h > MAX_FIELD_H {
+            return Err("field size over the capacity");
+        }
+
+        let mut field = Field::with_size(saved.w, saved.h);
+        for (y, row) in saved.rows.iter().enumerate() {
+            for (x, block) in row.iter().enumerate() {
+                if x < saved.w && y < saved.h {
+                    field.set_block(x as i8, y as i8, *block);
+                } else if block.is_filled() {
+                    return Err("block out of the field");
+                }
+            }
+        }
+
+        Ok(field)
+    }
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self::with_size(FIELD_W, FIELD_H)
+    }
 }
 
 /// y: 0 is the bottom
@@ -14,55 +128,213 @@ impl Field {
         Self::default()
     }
 
-    pub fn test(&self, x: i8, y: i8) -> bool {
-        let x = x as usize;
-        let y = y as usize;
+    /// w and h must not exceed MAX_FIELD_W and MAX_FIELD_H.
+    pub fn with_size(w: usize, h: usize) -> Self {
+        assert!(w <= MAX_FIELD_W && h <= MAX_FIELD_H);
+
+        let walls = !(((1 << w) - 1) << WALL_W);
+
+        Self {
+            w,
+            h,
+            walls,
+            bits: [walls; MAX_FIELD_H],
+            rows: [[Block::Empty; MAX_FIELD_W]; MAX_FIELD_H],
+        }
+    }
 
-        if x < 0 || FIELD_W <= x || y < 0 || FIELD_H <= y {
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    /// Return the bit of x in RowBits, or None when x is out of the bits.
+    fn bit(x: i8) -> Option<RowBits> {
+        let shift = x.checked_add(WALL_W)?;
+        if shift < 0 || RowBits::BITS as i8 <= shift {
+            return None;
+        }
+        Some(1 << shift)
+    }
+
+    pub fn test(&self, x: i8, y: i8) -> bool {
+        // negative values wrap around to large numbers
+        if self.h <= y as usize {
             return true;
         }
 
-        self.rows[y][x]
+        match Self::bit(x) {
+            Some(bit) => self.bits[y as usize] & bit != 0,
+            None => true,
+        }
+    }
+
+    /// Test cells at once by a mask for each row.
+    /// Return true if any cell hits.
+    pub fn test_cells(&self, (x, y): (i8, i8), cells: &[(i8, i8)]) -> bool {
+        let mut masks: [(i8, RowBits); 4] = Default::default();
+        let mut len = 0;
+
+        for (cell_x, cell_y) in cells.iter() {
+            let y = y + cell_y;
+            let bit = match Self::bit(x + cell_x) {
+                Some(bit) if (y as usize) < self.h => bit,
+                _ => return true,
+            };
+
+            match masks[..len].iter_mut().find(|(row, _)| *row == y) {
+                Some((_, mask)) => *mask |= bit,
+                None => {
+                    masks[len] = (y, bit);
+                    len += 1;
+                }
+            }
+        }
+
+        masks[..len]
+            .iter()
+            .any(|(y, mask)| self.bits[*y as usize] & mask != 0)
     }
 
+    /// Fill the cell as garbage.
     pub fn set(&mut self, x: i8, y: i8) {
-        self.rows[y as usize][x as usize] = true;
-        self.counts[y as usize] += 1;
+        self.set_block(x, y, Block::Garbage);
+    }
+
+    pub fn set_block(&mut self, x: i8, y: i8, block: Block) {
+        let bit = Self::bit(x).unwrap();
+        if block.is_filled() {
+            self.bits[y as usize] |= bit;
+        } else {
+            self.bits[y as usize] &= !bit;
+        }
+        self.rows[y as usize][x as usize] = block;
+    }
+
+    pub fn block(&self, x: i8, y: i8) -> Block {
+        self.rows[y as usize][x as usize]
+    }
+
+    /// FNV-1a of the size and the blocks, to compare fields cheaply.
+    pub fn digest(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut feed = |byte: u8| hash = (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+
+        feed(self.w as u8);
+        feed(self.h as u8);
+        for row in self.rows() {
+            for block in row.iter() {
+                feed(match block {
+                    Block::Empty => 0,
+                    Block::Mino(kind) => 1 + kind.index() as u8,
+                    Block::Garbage => 8,
+                });
+            }
+        }
+
+        hash
+    }
+
+    /// Count of filled cells in the row.
+    pub fn count(&self, y: i8) -> usize {
+        (self.bits[y as usize] & !self.walls).count_ones() as usize
     }
 
     pub fn is_filled(&self, y: i8) -> bool {
-        self.counts[y as usize] == FIELD_W
+        self.bits[y as usize] == FILLED_ROW
+    }
+
+    fn is_empty(&self, y: usize) -> bool {
+        self.bits[y] == self.walls
     }
 
     pub fn delete(&mut self, y: i8) -> bool {
-        if self.counts[y as usize] != FIELD_W {
+        if !self.is_filled(y) {
             return false;
         }
 
-        self.counts[y as usize] = 0;
-        self.rows[y as usize] = [false; FIELD_W];
+        self.bits[y as usize] = self.walls;
+        self.rows[y as usize] = [Block::Empty; MAX_FIELD_W];
 
         true
     }
 
-    /// MUST float from above after delete multiline
+    /// Rows filled entirely, which clear_lines will delete.
+    pub fn filled_rows(&self) -> ClearedRows {
+        let mut rows = ClearedRows::default();
+        for y in 0..self.h {
+            if self.bits[y] == FILLED_ROW {
+                rows.0 |= 1 << y;
+            }
+        }
+        rows
+    }
+
+    /// Delete all filled rows at once and float everything above them.
+    /// Return the deleted rows by indices before deleting.
+    pub fn clear_lines(&mut self) -> ClearedRows {
+        let cleared = self.filled_rows();
+        if cleared.is_empty() {
+            return cleared;
+        }
+
+        let mut to = self.h;
+        for from in (0..self.h).rev() {
+            if cleared.contains(from as i8) {
+                continue;
+            }
+
+            to -= 1;
+            if to != from {
+                self.bits[to] = self.bits[from];
+                self.rows[to] = self.rows[from];
+            }
+        }
+
+        for y in 0..to {
+            self.bits[y] = self.walls;
+            self.rows[y] = [Block::Empty; MAX_FIELD_W];
+        }
+
+        cleared
+    }
+
+    /// Return true if nothing remains after deleting filled lines.
+    pub fn is_perfect_clear(&self) -> bool {
+        self.bits[..self.h]
+            .iter()
+            .all(|bits| *bits == self.walls || *bits == FILLED_ROW)
+    }
+
+    /// MUST float from above after delete multiline.
+    /// clear_lines does both for all filled rows.
     pub fn float(&mut self, y: i8) {
         let mut now = y;
         while now > 0 {
             let up_row = now - 1;
-            if self.counts[up_row as usize] == 0 {
+            if self.is_empty(up_row as usize) {
                 return;
             }
 
             self.rows.swap(now as usize, up_row as usize);
-            self.counts.swap(now as usize, up_row as usize);
+            self.bits.swap(now as usize, up_row as usize);
 
             now -= 1;
         }
     }
 
-    pub fn rows(&self) -> &[[bool; FIELD_W]; FIELD_H] {
-        &self.rows
+    /// Cells of a row trimmed to the width.
+    pub fn row(&self, y: i8) -> &[Block] {
+        &self.rows[y as usize][..self.w]
+    }
+
+    /// Rows from the top, each trimmed to the width.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Block]> + ExactSizeIterator {
+        let w = self.w;
+        self.rows[..self.h].iter().map(move |row| &row[..w])
     }
 }
 
@@ -72,10 +344,10 @@ mod filed_tester {
     use std::prelude::v1::*;
 
     pub fn print_field(f: &Field, h: usize) -> String {
-        let mut canvas = vec![vec!["⬜"; FIELD_W]; h];
+        let mut canvas = vec![vec!["⬜"; f.width()]; h];
 
         for y in 0..h {
-            for x in 0..FIELD_W {
+            for x in 0..f.width() {
                 if f.test(x as i8, y as i8) {
                     canvas[y as usize][x as usize] = "⬛";
                 }
@@ -99,6 +371,26 @@ mod tests {
     use crate::*;
     use std::prelude::v1::*;
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_save_and_load() {
+        let mut field = Field::with_size(6, 10);
+        field.set(0, 9);
+        field.set_block(5, 8, Block::Mino(MinoKind::T));
+
+        let saved = serde_json::to_value(&field).unwrap();
+        let loaded: Field = serde_json::from_value(saved.clone()).unwrap();
+        assert_eq!(field, loaded);
+
+        let mut tall = saved.clone();
+        tall["h"] = (MAX_FIELD_H + 1).into();
+        assert!(serde_json::from_value::<Field>(tall).is_err());
+
+        let mut outside = saved;
+        outside["rows"][0][6] = serde_json::to_value(Block::Garbage).unwrap();
+        assert!(serde_json::from_value::<Field>(outside).is_err());
+    }
+
     #[test]
     fn test_float() {
         let mut f = Field::new();
@@ -109,7 +401,10 @@ mod tests {
         f.set(0, 3);
 
         let s = print_field(&f, 4);
-        assert_eq!([0, 1, 2, 1], f.counts[0..4]);
+        assert_eq!(
+            [0, 1, 2, 1],
+            [f.count(0), f.count(1), f.count(2), f.count(3)]
+        );
         assert_eq!(
             "\
                 ⬜⬜⬜⬜⬜⬜⬜⬜⬜⬜\n\
@@ -125,7 +420,10 @@ mod tests {
         f.float(2);
 
         let s = print_field(&f, 4);
-        assert_eq!([0, 2, 1, 1], f.counts[0..4]);
+        assert_eq!(
+            [0, 2, 1, 1],
+            [f.count(0), f.count(1), f.count(2), f.count(3)]
+        );
         assert_eq!(
             "\
                 ⬜⬜⬜⬜⬜⬜⬜⬜⬜⬜\n\
@@ -139,7 +437,10 @@ mod tests {
         f.float(3);
 
         let s = print_field(&f, 4);
-        assert_eq!([0, 1, 2, 1], f.counts[0..4]);
+        assert_eq!(
+            [0, 1, 2, 1],
+            [f.count(0), f.count(1), f.count(2), f.count(3)]
+        );
         assert_eq!(
             "\
                 ⬜⬜⬜⬜⬜⬜⬜⬜⬜⬜\n\
@@ -151,6 +452,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_float_keeps_blocks() {
+        let mut f = Field::new();
+
+        f.set_block(2, 1, Block::Mino(MinoKind::T));
+        f.set_block(3, 1, Block::Mino(MinoKind::I));
+        for x in 0..(FIELD_W as i8) {
+            f.set_block(x, 2, Block::Mino(MinoKind::S));
+        }
+
+        assert_eq!(true, f.delete(2));
+        f.float(2);
+
+        assert_eq!(Block::Empty, f.block(2, 1));
+        assert_eq!(Block::Mino(MinoKind::T), f.block(2, 2));
+        assert_eq!(Block::Mino(MinoKind::I), f.block(3, 2));
+        assert_eq!(Block::Empty, f.block(4, 2));
+        assert_eq!(2, f.count(2));
+    }
+
     #[test]
     fn test_delete() {
         let mut f = Field::new();
@@ -172,7 +493,7 @@ mod tests {
 
         assert_eq!(false, f.delete(1));
         let s = print_field(&f, 3);
-        assert_eq!(1, f.counts[1]);
+        assert_eq!(1, f.count(1));
         assert_eq!(
             "\
                 ⬜⬜⬜⬜⬜⬜⬜⬜⬜⬜\n\
@@ -184,7 +505,7 @@ mod tests {
 
         assert_eq!(true, f.delete(2));
         let s = print_field(&f, 3);
-        assert_eq!(0, f.counts[0]);
+        assert_eq!(0, f.count(0));
         assert_eq!(
             "\
                 ⬜⬜⬜⬜⬜⬜⬜⬜⬜⬜\n\
@@ -194,4 +515,59 @@ mod tests {
             s
         );
     }
+
+    #[test]
+    fn test_sized_field() {
+        let mut f = Field::with_size(4, MAX_FIELD_H);
+
+        assert_eq!(false, f.test(3, MAX_FIELD_H as i8 - 1));
+        assert_eq!(true, f.test(4, 0));
+        assert_eq!(true, f.test(0, MAX_FIELD_H as i8));
+
+        for x in 0..4 {
+            f.set(x, 40);
+        }
+        assert_eq!(true, f.is_filled(40));
+        assert_eq!(4, f.row(40).len());
+        assert_eq!(MAX_FIELD_H, f.rows().len());
+        assert!(f.rows().all(|row| row.len() == 4));
+    }
+
+    #[test]
+    fn test_clear_lines() {
+        let mut f = Field::new();
+
+        f.set(1, 1);
+        for y in [2, 4, 5].iter() {
+            for x in 0..(FIELD_W as i8) {
+                f.set(x, *y);
+            }
+        }
+        f.set(3, 3);
+
+        assert_eq!(3, f.filled_rows().len());
+
+        let cleared = f.clear_lines();
+        assert_eq!(vec![2, 4, 5], cleared.iter().collect::<Vec<_>>());
+        assert_eq!(Block::Garbage, f.block(1, 4));
+        assert_eq!(Block::Garbage, f.block(3, 5));
+        assert_eq!([0, 0, 0, 0, 1, 1], [0, 1, 2, 3, 4, 5].map(|y| f.count(y)));
+        assert!(f.clear_lines().is_empty());
+    }
+
+    #[test]
+    fn test_cells() {
+        let mut f = Field::new();
+        f.set(3, 5);
+
+        let cells = [(-1, 0), (0, 0), (1, 0), (2, 0)];
+        assert_eq!(false, f.test_cells((1, 0), &cells));
+        assert_eq!(false, f.test_cells((7, 5), &cells));
+        assert_eq!(true, f.test_cells((0, 0), &cells));
+        assert_eq!(true, f.test_cells((8, 0), &cells));
+        assert_eq!(true, f.test_cells((2, 5), &cells));
+        assert_eq!(true, f.test_cells((1, -1), &cells));
+        assert_eq!(true, f.test_cells((1, FIELD_H as i8), &cells));
+        assert_eq!(true, f.test_cells((100, 0), &cells));
+    }
 }
@@ -0,0 +1,96 @@
+use crate::*;
+
+// Pieces searched for the minimum. Minos rarely leave the spawn row without soft drop.
+const MAX_FINESSE_NODES: usize = 256;
+
+/// Count of moves and rotations at least to lock a mino at the cells by Event::Land,
+/// starting from the spawned piece without soft drop.
+/// Cells are compared as a set, so MinoI, MinoS and MinoZ may end in either state.
+/// None if the cells are not reachable so.
+pub fn finesse_minimum<S: RotationSystem + ?Sized>(
+    field: &Field,
+    system: &S,
+    spawned: Piece,
+    cells: &[(i8, i8); 4],
+) -> Option<usize> {
+    let mut queue = [(spawned, 0); MAX_FINESSE_NODES];
+    let (mut head, mut tail) = (0, 1);
+
+    if !fits(field, spawned) {
+        return None;
+    }
+
+    while head < tail {
+        let (piece, inputs) = queue[head];
+        head += 1;
+
+        let landed = drop_to_bottom(field, piece).cells();
+        if landed.iter().all(|cell| cells.contains(cell)) {
+            return Some(inputs);
+        }
+
+        let events = [
+            Event::MoveL,
+            Event::MoveR,
+            Event::RotateR,
+            Event::RotateL,
+            Event::Rotate180,
+        ];
+        for event in events.iter() {
+            let next = match event {
+                Event::MoveL => shift(field, piece, (-1, 0)),
+                Event::MoveR => shift(field, piece, (1, 0)),
+                _ => rotate(field, system, piece, *event),
+            };
+
+            if let Some(next) = next {
+                if tail < MAX_FINESSE_NODES && queue[..tail].iter().all(|(p, _)| *p != next) {
+                    queue[tail] = (next, inputs + 1);
+                    tail += 1;
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn spawn(kind: MinoKind) -> Piece {
+        Piece::new(kind, Rotation::State0, MINO_FIRST_POSITION)
+    }
+
+    fn minimum(target: Piece) -> Option<usize> {
+        let field = Field::new();
+        finesse_minimum(&field, &Srs, spawn(target.kind), &target.cells())
+    }
+
+    #[test]
+    fn test_finesse_minimum() {
+        assert_eq!(
+            Some(0),
+            minimum(Piece::new(MinoKind::T, Rotation::State0, (4, 21)))
+        );
+        assert_eq!(
+            Some(1),
+            minimum(Piece::new(MinoKind::T, Rotation::State2, (4, 20)))
+        );
+        assert_eq!(
+            Some(4),
+            minimum(Piece::new(MinoKind::T, Rotation::StateL, (1, 20)))
+        );
+
+        // the same cells as StateR at the next column
+        let vertical = Piece::new(MinoKind::S, Rotation::StateL, (5, 20));
+        assert_eq!(Some(1), minimum(vertical));
+
+        // floating
+        assert_eq!(
+            None,
+            minimum(Piece::new(MinoKind::T, Rotation::State0, (4, 10)))
+        );
+    }
+}
@@ -1,48 +1,153 @@
 use crate::*;
 use core::iter::repeat;
+use core::ops::Deref;
 
-use rand::prelude::SmallRng;
-use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
+use rand::SeedableRng;
 
-pub struct Game<F: FnMut(GameEvent)> {
-    callback: F,
-    rng: SmallRng,
+#[derive(Clone)]
+pub struct Game<S: EventSink = EventQueue> {
+    sink: S,
+    config: GameConfig,
+    state: GameState,
+}
 
-    // Indexes of MINOS_SRC that has 2 laps of shuffled 7 minos.
-    // 7..14 is a next lap.
-    minos_index: [usize; 14],
+/// Whole simulation of Game without the sink and the config.
+/// Copy it to branch or roll back, and put it back by Game::restore.
+/// With the serde feature, it can be saved to resume later.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameState {
+    rng: GameRng,
 
-    // Point a next mino.
-    // Always 0..6.
-    minos_position: usize,
+    // Generates the order from the rng. Copied from the config at the start.
+    randomizer: RandomizerRule,
+    // Minos drawn from the randomizer. 0 is a next mino.
+    next_queue: [MinoKind; MAX_PREVIEW],
 
     // A mino user is controlling.
     // Option is just for handling multiple mutable ownership in a struct.
     // Always mino is a Some.
     mino: Option<MinoAggregation>,
 
+    // A mino kept aside by Event::Hold. It is always a first state in MINOS_SRC.
+    hold: Option<MinoAggregation>,
+    // Hold is allowed once until the mino is locked.
+    holdable: bool,
+
     field: Field,
 
     alive: bool,
-    is_landing: bool,
     is_locked: bool,
-    landing_wait_count: u8,
+    // Event::TimeGo from the start.
+    frames: u32,
+    // The lock delay of the mino.
+    lock: LockProgress,
     locking_wait_count: u8,
+    // The last successful action is a rotation.
     spun: bool,
+    // Index of the SRS offsets that the last rotation used.
+    kick_index: usize,
+    // The last rotation is Event::Rotate180, whose kicks have no fin kick.
+    half_turn: bool,
+
+    // The mino just after spawn and count of moves and rotations since then.
+    spawned: Piece,
+    inputs: usize,
+
+    // Accumulated gravity in 1 / GRAVITY_UNIT cells.
+    gravity_count: u32,
 
-    delete_row: [i8; 4],
+    // The last line clear is Tetris or T-spin.
+    back_to_back: bool,
+    // Of the last lock, informed after the line clear delay.
+    line_clear: LineClear,
 
     score: Score,
 }
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Score {
+    pub level: usize,
+    pub point: usize,
+    // Count of sequential locks that delete lines.
+    pub combo: usize,
+    // Count of line clears given back-to-back bonus.
+    pub back_to_back: usize,
+    pub perfect_clear: usize,
+
     pub deleted_line: usize,
+    pub t_spin_zero: usize,
     pub t_spin1: usize,
     pub t_spin2: usize,
     pub t_spin3: usize,
+    pub t_spin_mini_zero: usize,
+    pub t_spin_mini1: usize,
+    pub t_spin_mini2: usize,
     pub tetris: usize,
+
+    // Count of minos locked with more inputs than needed.
+    pub finesse_fault: usize,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TSpin {
+    None,
+    Mini,
+    Full,
+}
+
+/// Type of a line clear such as single, Tetris, T-spin mini or perfect clear.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineClear {
+    pub lines: usize,
+    pub t_spin: TSpin,
+    pub perfect_clear: bool,
+}
+
+impl Default for LineClear {
+    fn default() -> Self {
+        Self {
+            lines: 0,
+            t_spin: TSpin::None,
+            perfect_clear: false,
+        }
+    }
+}
+
+// The last SRS offset for MinoT (so called "TST kick" or "fin kick")
+// makes T-spin mini a full T-spin.
+const T_SPIN_FIN_KICK_INDEX: usize = 4;
+
+// Points for deleted lines (index) multiplied by level.
+const POINTS: [usize; 5] = [0, 100, 300, 500, 800];
+const T_SPIN_POINTS: [usize; 4] = [400, 800, 1200, 1600];
+const T_SPIN_MINI_POINTS: [usize; 3] = [100, 200, 400];
+const PERFECT_CLEAR_POINTS: [usize; 5] = [0, 800, 1200, 1800, 2000];
+const BACK_TO_BACK_PERFECT_CLEAR_TETRIS_POINT: usize = 3200;
+const COMBO_POINT: usize = 50;
+
+// Corners around the center of MinoT.
+const T_SPIN_CORNERS: [(i8, i8); 4] = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+impl GameState {
+    pub fn mino(&self) -> &MinoAggregation {
+        self.mino.as_ref().unwrap()
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
 }
 
 impl Score {
@@ -51,89 +156,218 @@ impl Score {
     }
 }
 
-impl<F: FnMut(GameEvent)> Game<F> {
-    pub fn new(seed: [u8; 16], callback: F) -> Self {
-        let mut rng = SmallRng::from_seed(seed);
+impl Game<EventQueue> {
+    /// Game buffering events instead of calling back. Poll them after each step.
+    pub fn with_queue(seed: [u8; 16], config: GameConfig) -> Result<Self, ConfigError> {
+        Self::new(seed, config, EventQueue::new())
+    }
+
+    pub fn poll_event(&mut self) -> Option<GameEvent> {
+        self.sink.pop()
+    }
+
+    pub fn drain_events(&mut self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.sink.drain()
+    }
+
+    /// Count of events dropped by the full queue. Nothing is dropped while polled after each step.
+    pub fn dropped_events(&self) -> usize {
+        self.sink.dropped()
+    }
+}
+
+impl<S: EventSink> Game<S> {
+    pub fn new(seed: [u8; 16], config: GameConfig, sink: S) -> Result<Self, ConfigError> {
+        config.validate()?;
 
-        let mut minos_index = [0, 1, 2, 3, 4, 5, 6, 0, 1, 2, 3, 4, 5, 6];
+        Ok(Game {
+            sink,
+            config,
+            state: GameState {
+                rng: GameRng::from_seed(seed),
 
-        Game {
-            callback,
-            rng,
+                randomizer: config.randomizer,
+                next_queue: [MinoKind::I; MAX_PREVIEW],
 
-            minos_index,
-            minos_position: 0,
+                mino: Some(MINOS_SRC[0]),
 
-            mino: Some(MINOS_SRC[0]),
+                hold: None,
+                holdable: true,
 
-            field: Field::new(),
+                field: Field::with_size(config.field_w, config.field_h),
 
-            alive: false,
-            is_landing: false,
-            is_locked: false,
-            landing_wait_count: 0,
-            locking_wait_count: 0,
-            spun: false,
+                alive: false,
+                is_locked: false,
+                frames: 0,
+                lock: LockProgress::new(config.mino_first_position.1),
+                locking_wait_count: 0,
+                spun: false,
+                kick_index: 0,
+                half_turn: false,
 
-            delete_row: [-1; 4],
+                spawned: Piece::from(MINOS_SRC[0]),
+                inputs: 0,
 
-            score: Default::default(),
-        }
+                gravity_count: 0,
+                back_to_back: false,
+                line_clear: LineClear::default(),
+
+                score: Default::default(),
+            },
+        })
     }
 
     pub fn start(&mut self) {
-        self.minos_index[0..6].shuffle(&mut self.rng);
-        self.minos_index[7..14].shuffle(&mut self.rng);
-        self.minos_position = 0;
+        self.state.randomizer = self.config.randomizer;
+        for i in 0..MAX_PREVIEW {
+            self.state.next_queue[i] = self.state.randomizer.next(&mut self.state.rng);
+        }
 
-        self.mino = self.new_mino();
+        self.state.field = Field::with_size(self.config.field_w, self.config.field_h);
 
-        self.field = Field::new();
+        self.state.hold = None;
+        self.state.holdable = true;
 
-        self.alive = true;
-        self.is_landing = false;
-        self.landing_wait_count = 0;
-        self.spun = false;
+        self.state.alive = true;
+        self.state.frames = 0;
+        self.reset_previous_state();
 
-        self.score = Score::new();
+        self.state.back_to_back = false;
+
+        self.state.score = Score::new();
+        self.state.score.level = level_by_lines(0);
 
-        self.inform_next();
-        self.inform_score_change();
         self.inform_game_start();
+        self.inform_score_change();
+
+        // informs Next and Spawned
+        self.state.mino = self.new_mino();
     }
 
     pub fn mino(&self) -> &MinoAggregation {
-        &self.mino.as_ref().unwrap()
+        &self.state.mino.as_ref().unwrap()
+    }
+
+    pub fn hold(&self) -> Option<&MinoAggregation> {
+        self.state.hold.as_ref()
+    }
+
+    /// Next minos as many as GameConfig::preview.
+    pub fn next_queue(&self) -> &[MinoKind] {
+        &self.state.next_queue[..self.config.preview]
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.state.alive
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.state.frames
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.state.score
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Copy of the whole simulation.
+    pub fn snapshot(&self) -> GameState {
+        self.state
+    }
+
+    /// Continue from a snapshot taken with the same config.
+    pub fn restore(&mut self, state: GameState) {
+        self.state = state;
+    }
+
+    pub fn field(&self) -> &Field {
+        &self.state.field
     }
 
     pub fn field_mut(&mut self) -> &mut Field {
-        &mut self.field
+        &mut self.state.field
     }
 
-    pub fn rows(&self) -> &[[bool; FIELD_W]; FIELD_H] {
-        self.field.rows()
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Block]> + ExactSizeIterator {
+        self.state.field.rows()
     }
 
     pub fn new_mino(&mut self) -> Option<MinoAggregation> {
         let mino = self.next_mino();
         self.forward_minos_position();
         self.inform_next();
-        Some(mino)
+        self.spawn(mino)
+    }
+
+    /// Put a new mino into the field by the rotation system and SpawnRule.
+    fn spawn(&mut self, mino: MinoAggregation) -> Option<MinoAggregation> {
+        let kind = mino.kind();
+        let rotation = self.config.rotation.spawn_rotation(kind);
+        let (x, y) = self.config.mino_first_position;
+        let (offset_x, offset_y) = self.config.rotation.state_offset(kind, rotation);
+        let mut mino = MinoAggregation::new(kind, rotation, (x + offset_x, y + offset_y));
+
+        let spawned = match_mino_state!(&mut mino, m => self.try_spawn(m));
+
+        if spawned {
+            self.state.spawned = Piece::from(mino);
+            self.state.inputs = 0;
+            self.inform(GameEvent::Spawned(self.state.spawned));
+            Some(mino)
+        } else if self.config.mode.tops_out() {
+            self.game_over()
+        } else {
+            self.state.field = Field::with_size(self.config.field_w, self.config.field_h);
+            self.spawn(mino)
+        }
+    }
+
+    fn try_spawn(&self, mino: &mut impl MinoFn) -> bool {
+        // Zen never tops out, so it always needs a free place to reset the field.
+        let block_out = self.config.spawn.block_out || !self.config.mode.tops_out();
+        if block_out && mino.test_with_field(&self.state.field) {
+            return false;
+        }
+
+        if self.config.spawn.drop_on_spawn {
+            self.try_move(mino, OFFSET_DOWN);
+        }
+
+        true
     }
 
     fn next_mino(&self) -> MinoAggregation {
-        let index = self.minos_index[self.minos_position as usize] as usize;
-        MINOS_SRC[index]
+        MINOS_SRC[self.state.next_queue[0].index()]
+    }
+
+    /// Stop the game when the goal of the mode is reached.
+    fn check_finished(&mut self) {
+        if self.state.alive
+            && self
+                .config
+                .mode
+                .is_finished(self.state.frames, &self.state.score)
+        {
+            self.state.alive = false;
+            self.inform_finished();
+        }
     }
 
     fn game_over(&mut self) -> Option<MinoAggregation> {
-        self.alive = false;
+        self.state.alive = false;
         self.inform_game_over();
         None
     }
 
     fn inform(&mut self, event: GameEvent) {
-        (self.callback)(event)
+        self.sink.receive(event)
     }
 
     fn inform_game_start(&mut self) {
@@ -144,253 +378,509 @@ impl<F: FnMut(GameEvent)> Game<F> {
         self.inform(GameEvent::Overflow);
     }
 
+    fn inform_finished(&mut self) {
+        let result = GameResult {
+            mode: self.config.mode,
+            frames: self.state.frames,
+            score: self.state.score.clone(),
+        };
+        self.inform(GameEvent::Finished(result));
+    }
+
     fn inform_score_change(&mut self) {
-        self.inform(GameEvent::ScoreChange(self.score.clone()));
+        self.inform(GameEvent::ScoreChange(self.state.score.clone()));
+    }
+
+    fn inform_hold(&mut self, kind: MinoKind) {
+        self.inform(GameEvent::Hold(kind));
+    }
+
+    fn inform_lines_cleared(&mut self, rows: ClearedRows) {
+        self.inform(GameEvent::LinesCleared(rows, self.state.line_clear));
+    }
+
+    fn inform_finesse_fault(&mut self, inputs: usize, minimum: usize) {
+        self.inform(GameEvent::FinesseFault { inputs, minimum });
     }
 
     fn inform_next(&mut self) {
-        // to avoid borrow checker
-        (self.callback)(GameEvent::Next(
-            &self.minos_index[self.minos_position..self.minos_position + 3],
-        ));
+        let next = NextMinos::new(&self.state.next_queue[..self.config.preview]);
+        self.inform(GameEvent::Next(next));
     }
 
     fn forward_minos_position(&mut self) {
-        self.minos_position += 1;
+        self.state.next_queue.copy_within(1.., 0);
+        self.state.next_queue[MAX_PREVIEW - 1] = self.state.randomizer.next(&mut self.state.rng);
+    }
 
-        if self.minos_position != 7 {
-            return;
+    /// Proceed a frame.
+    fn tick(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
+        let gravity = gravity(self.state.score.level);
+        self.state.gravity_count += gravity;
+
+        // straight to the bottom however tall the field is
+        let to_bottom = gravity >= GRAVITY_20G;
+        while to_bottom || self.state.gravity_count >= GRAVITY_UNIT {
+            if !to_bottom {
+                self.state.gravity_count -= GRAVITY_UNIT;
+            }
+            if let Err(_) = self.move_mino(mino, OFFSET_DOWN) {
+                break;
+            }
+            self.reset_by_falling(mino.pos().1);
         }
 
-        for i in 0..7 {
-            self.minos_index.swap(i, i + 7)
+        if self.can_fall(mino) {
+            return None;
         }
 
-        self.minos_index[7..14].shuffle(&mut self.rng);
-        self.minos_position = 0;
+        self.state.gravity_count = 0;
+        self.wait_locking(mino)
     }
 
-    fn wait_locking(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
-        self.landing_wait_count += 1;
+    fn can_fall(&self, mino: &mut impl MinoFn) -> bool {
+        let result = self.try_move(mino, OFFSET_DOWN);
+        if let Ok(_) = result {
+            mino.offset(OFFSET_DOWN.minus);
+        }
+        result.is_ok()
+    }
 
-        if self.landing_wait_count > LOCKING_TIME {
+    fn wait_locking(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
+        if self.state.lock.wait(&self.config.lock_delay) {
             self.lock(mino)
         } else {
             None
         }
     }
 
+    fn reset_by_falling(&mut self, y: i8) {
+        self.state.spun = false;
+        self.state.lock.fall(y);
+    }
+
+    fn reset_by_moving(&mut self) {
+        self.state.lock.reset_by_moving(&self.config.lock_delay);
+    }
+
+    /// For a new mino.
     fn reset_previous_state(&mut self) -> Option<MinoAggregation> {
-        self.spun = false;
-        self.is_landing = false;
-        self.landing_wait_count = 0;
+        self.state.spun = false;
+        self.state.kick_index = 0;
+        self.state.half_turn = false;
+        self.state.lock = LockProgress::new(self.config.mino_first_position.1);
+        self.state.gravity_count = 0;
         None
     }
 
     /// return false if all cells are out of display
     fn test_mino_in_display(&mut self, mino: &mut impl MinoFn) -> bool {
-        if mino.pos().1 >= FIELD_TOP {
+        let top = self.config.field_top();
+        if mino.pos().1 >= top {
             true
         } else {
-            mino.test_with_absolute_cells(|_, y| y >= FIELD_TOP)
+            mino.test_with_absolute_cells(|_, y| y >= top)
+        }
+    }
+
+    /// Detect T-spin by 3-corner rule.
+    /// MUST call before the mino is set to the field.
+    fn detect_t_spin(&self, mino: &impl MinoFn) -> TSpin {
+        if !self.state.spun || mino.kind() != MinoKind::T {
+            return TSpin::None;
+        }
+
+        let (x, y) = mino.pos();
+        let is_filled =
+            |(offset_x, offset_y): &(i8, i8)| self.state.field.test(x + offset_x, y + offset_y);
+
+        if T_SPIN_CORNERS.iter().filter(|c| is_filled(c)).count() < 3 {
+            return TSpin::None;
+        }
+
+        // corners at the side of the pointing cell
+        let front = match (mino.is_0(), mino.is_r(), mino.is_l(), mino.is_2()) {
+            (true, _, _, _) => [(-1, -1), (1, -1)],
+            (_, true, _, _) => [(1, -1), (1, 1)],
+            (_, _, true, _) => [(-1, -1), (-1, 1)],
+            _ => [(-1, 1), (1, 1)],
+        };
+
+        let fin_kick = !self.state.half_turn && self.state.kick_index == T_SPIN_FIN_KICK_INDEX;
+        if front.iter().all(is_filled) || fin_kick {
+            TSpin::Full
+        } else {
+            TSpin::Mini
         }
     }
 
-    // TODO: detect Tetris or T-spin, etc.
     fn lock(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
-        if !self.test_mino_in_display(mino) {
+        if self.config.spawn.lock_out
+            && self.config.mode.tops_out()
+            && !self.test_mino_in_display(mino)
+        {
             return self.game_over();
         }
 
-        self.is_locked = true;
+        let t_spin = self.detect_t_spin(mino);
+        let finesse = self.check_finesse(mino);
 
-        let mut filled_count = 0;
-        self.delete_row = [-1; 4];
+        self.state.is_locked = true;
+        self.state.holdable = true;
 
-        mino.mut_with_absolute_cells(|x, y| {
-            self.field.set(x, y);
-            if self.field.is_filled(y) {
-                self.delete_row[filled_count] = y;
-                filled_count += 1;
-            }
-        });
+        let block = Block::Mino(mino.kind());
+        mino.mut_with_absolute_cells(|x, y| self.state.field.set_block(x, y, block));
+        self.inform(GameEvent::Locked(mino.absolute_cells()));
 
-        if filled_count != 0 {
-            self.score.deleted_line += filled_count;
-
-            if filled_count == 4 {
-                self.score.tetris += 1;
-            } else if self.spun {
-                match filled_count {
-                    1 => self.score.t_spin1 += 1,
-                    2 => self.score.t_spin2 += 1,
-                    3 => self.score.t_spin3 += 1,
-                    _ => unreachable!(),
-                }
+        // cleared by erase after the delay
+        let filled_count = self.state.field.filled_rows().len();
+
+        let previous = self.state.score.clone();
+        let previous_back_to_back = self.state.back_to_back;
+        let perfect_clear = filled_count != 0 && self.state.field.is_perfect_clear();
+        self.add_point(t_spin, filled_count, perfect_clear);
+        self.state.line_clear = LineClear {
+            lines: filled_count,
+            t_spin,
+            perfect_clear,
+        };
+
+        if filled_count != 0 || t_spin != TSpin::None {
+            self.state.score.deleted_line += filled_count;
+            self.state.score.level = level_by_lines(self.state.score.deleted_line);
+
+            match (t_spin, filled_count) {
+                (TSpin::None, 4) => self.state.score.tetris += 1,
+                (TSpin::None, _) => {}
+                (TSpin::Full, 0) => self.state.score.t_spin_zero += 1,
+                (TSpin::Full, 1) => self.state.score.t_spin1 += 1,
+                (TSpin::Full, 2) => self.state.score.t_spin2 += 1,
+                (TSpin::Full, 3) => self.state.score.t_spin3 += 1,
+                (TSpin::Mini, 0) => self.state.score.t_spin_mini_zero += 1,
+                (TSpin::Mini, 1) => self.state.score.t_spin_mini1 += 1,
+                (TSpin::Mini, 2) => self.state.score.t_spin_mini2 += 1,
+                _ => unreachable!(),
             }
+        }
+
+        if finesse.is_some() {
+            self.state.score.finesse_fault += 1;
+        }
+
+        if self.state.score.combo != previous.combo {
+            self.inform(GameEvent::ComboChanged(self.state.score.combo));
+        }
+
+        if self.state.back_to_back != previous_back_to_back {
+            self.inform(GameEvent::BackToBackChanged(self.state.back_to_back));
+        }
 
+        if self.state.score != previous {
             self.inform_score_change();
         }
 
+        if let Some(minimum) = finesse {
+            self.inform_finesse_fault(self.state.inputs, minimum);
+        }
+
+        self.check_finished();
+
         self.reset_previous_state();
 
         None
     }
 
+    /// Return the minimum count if the mino took more inputs than it.
+    /// Minos which need soft drop are not judged.
+    /// MUST call before the mino is set to the field.
+    fn check_finesse(&self, mino: &impl MinoFn) -> Option<usize> {
+        let cells = mino.absolute_cells();
+        let minimum = finesse_minimum(
+            &self.state.field,
+            &self.config.rotation,
+            self.state.spawned,
+            &cells,
+        )?;
+
+        if self.state.inputs > minimum {
+            Some(minimum)
+        } else {
+            None
+        }
+    }
+
+    fn add_point(&mut self, t_spin: TSpin, lines: usize, perfect_clear: bool) {
+        if lines == 0 {
+            self.state.score.combo = 0;
+        } else {
+            self.state.score.combo += 1;
+        }
+
+        let mut point = match t_spin {
+            TSpin::None => POINTS[lines],
+            TSpin::Mini => T_SPIN_MINI_POINTS[lines],
+            TSpin::Full => T_SPIN_POINTS[lines],
+        };
+
+        // T-spin without deleting lines neither keeps nor breaks back-to-back.
+        if lines != 0 {
+            let difficult = lines == 4 || t_spin != TSpin::None;
+            let back_to_back = difficult && self.state.back_to_back;
+
+            if back_to_back {
+                point = point * 3 / 2;
+                self.state.score.back_to_back += 1;
+            }
+
+            if self.state.score.combo > 1 {
+                point += COMBO_POINT * (self.state.score.combo - 1);
+            }
+
+            if perfect_clear {
+                point += if back_to_back && lines == 4 {
+                    BACK_TO_BACK_PERFECT_CLEAR_TETRIS_POINT
+                } else {
+                    PERFECT_CLEAR_POINTS[lines]
+                };
+                self.state.score.perfect_clear += 1;
+            }
+
+            self.state.back_to_back = difficult;
+        }
+
+        self.state.score.point += point * self.state.score.level;
+    }
+
     fn erase(&mut self) -> Option<MinoAggregation> {
-        if self.locking_wait_count < LOCKING_WAIT_TIME {
-            self.locking_wait_count += 1;
+        if self.state.locking_wait_count < self.config.line_clear_delay {
+            self.state.locking_wait_count += 1;
             return None;
         }
 
-        self.is_locked = false;
-        self.locking_wait_count = 0;
+        self.state.is_locked = false;
+        self.state.locking_wait_count = 0;
 
-        for row in self.delete_row.iter().rev().copied() {
-            if row != -1 {
-                self.field.delete(row);
-                self.field.float(row);
-            }
+        let cleared = self.state.field.clear_lines();
+        if !cleared.is_empty() {
+            self.inform_lines_cleared(cleared);
         }
 
         self.new_mino()
     }
 
-    fn land(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
-        // lock when cannot move down at all
-        if let Err(_) = self.try_move(mino, OFFSET_DOWN) {
-            return self.lock(mino);
+    /// Swap the mino with the held one, or take a next mino when nothing is held.
+    fn hold_mino(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
+        if !self.state.holdable {
+            return None;
         }
 
-        loop {
-            if let Err(_) = self.try_move(mino, OFFSET_DOWN) {
-                break;
-            }
+        self.state.holdable = false;
+        self.reset_previous_state();
+
+        let kind = mino.kind();
+        let held = self.state.hold.replace(MINOS_SRC[kind.index()]);
+        self.inform_hold(kind);
+
+        match held {
+            Some(held) => self.spawn(held),
+            None => self.new_mino(),
         }
+    }
+
+    fn land(&mut self, mino: &mut impl MinoFn) -> Option<MinoAggregation> {
+        // keep previous state when cannot move down at all
+        if self.drop_to_bottom(mino) != 0 {
+            self.state.spun = false;
+        }
+        self.inform(GameEvent::Landed(piece_of(mino)));
 
-        self.reset_previous_state();
         self.lock(mino)
     }
 
-    fn try_move(&mut self, moving: &mut impl MinoFn, offset: Offset) -> Result<(), ()> {
+    /// Return moved distance.
+    fn drop_to_bottom(&self, mino: &mut impl MinoFn) -> usize {
+        let mut distance = 0;
+        while let Ok(_) = self.try_move(mino, OFFSET_DOWN) {
+            distance += 1;
+        }
+        distance
+    }
+
+    /// Return cells where the mino will be locked by Event::Land.
+    /// For rendering a ghost piece.
+    pub fn ghost_cells(&self) -> [(i8, i8); 4] {
+        let mut ghost = *self.mino();
+        match_mino_state!(&mut ghost, m => self.ghost_cells_of(m))
+    }
+
+    fn ghost_cells_of(&self, ghost: &mut impl MinoFn) -> [(i8, i8); 4] {
+        self.drop_to_bottom(ghost);
+        ghost.absolute_cells()
+    }
+
+    /// try_move informing the move, and the landing if it is down onto the ground.
+    fn move_mino(&mut self, moving: &mut impl MinoFn, offset: Offset) -> Result<(), ()> {
+        let down = offset.plus == OFFSET_DOWN.plus;
+        self.try_move(moving, offset)?;
+
+        self.inform(GameEvent::Moved(piece_of(moving)));
+        if down && !self.can_fall(moving) {
+            self.inform(GameEvent::Landed(piece_of(moving)));
+        }
+        Ok(())
+    }
+
+    fn try_move(&self, moving: &mut impl MinoFn, offset: Offset) -> Result<(), ()> {
         moving.offset(offset.plus);
-        if moving.test_with_absolute_cells(|x, y| self.field.test(x, y)) {
+        if moving.test_with_field(&self.state.field) {
             moving.offset(offset.minus);
             return Err(());
         }
         Ok(())
     }
 
+    /// Search placements of the current mino by the rotation system of the config.
+    pub fn generate_moves(&self, generator: &mut MoveGenerator, options: MoveOptions) -> usize {
+        generator.generate(
+            &self.state.field,
+            &self.config.rotation,
+            Piece::from(*self.mino()),
+            options,
+        )
+    }
+
+    /// Events from the current mino to lock it as the target, the shortest if possible.
+    /// Gravity is not followed, so step the events before the mino falls.
+    /// Frames between the events run the lock delay, so the events keep the mino
+    /// from locking on the way with Event::TimeGo after each of them.
+    pub fn find_path(
+        &self,
+        generator: &mut MoveGenerator,
+        target: Piece,
+        options: MoveOptions,
+    ) -> Result<Path, PathError> {
+        generator.generate_locking(
+            &self.state.field,
+            &self.config.rotation,
+            Piece::from(*self.mino()),
+            options,
+            self.config.lock_delay,
+            self.state.lock,
+        );
+        if let Some(path) = generator.path_to(target) {
+            return Ok(path);
+        }
+
+        self.generate_moves(generator, options);
+        match generator.path_to(target) {
+            Some(_) => Err(PathError::LockResets),
+            None => Err(PathError::Unreachable),
+        }
+    }
+
+    /// Try kicks of the rotation system in order.
     fn try_rotate(
         &mut self,
-        mut rotated: impl WithCell + Into<MinoAggregation>,
-        offsets: &[(i8, i8)],
+        from: Rotation,
+        mut rotated: impl MinoFn,
+        half_turn: bool,
     ) -> Result<MinoAggregation, ()> {
-        let (x, y) = rotated.pos();
-        for (offset_x, offset_y) in offsets {
-            rotated.absolute((x + offset_x, y + offset_y));
-            if !rotated.test_with_absolute_cells(|x, y| self.field.test(x, y)) {
-                self.reset_previous_state();
-                self.spun = true;
-                return Ok(rotated.into());
-            }
-        }
-        Err(())
+        let i = kick(&self.config.rotation, &self.state.field, from, &mut rotated).ok_or(())?;
+
+        self.reset_by_moving();
+        self.state.lock.fall(rotated.pos().1);
+        self.state.spun = true;
+        self.state.kick_index = i;
+        self.state.half_turn = half_turn;
+        self.inform(GameEvent::Rotated {
+            piece: piece_of(&rotated),
+            kick: i,
+        });
+        Ok(rotated.into())
     }
 
     pub fn step(&mut self, event: impl Into<Event>) {
-        if self.is_locked {
+        if !self.state.alive {
+            return;
+        }
+
+        if self.state.is_locked {
             match self.erase() {
                 None => {}
-                Some(mino) => self.mino = Some(mino),
+                Some(mino) => self.state.mino = Some(mino),
             }
             return;
         }
 
         let event = event.into();
+        match event {
+            Event::MoveR | Event::MoveL | Event::RotateR | Event::RotateL | Event::Rotate180 => {
+                self.state.inputs += 1
+            }
+            _ => {}
+        }
 
-        let mut mino = self.mino.take().unwrap();
-        let next = match &mut mino {
-            MinoAggregation::Is0(m) => self.action(m, event),
-            MinoAggregation::Os0(m) => self.action(m, event),
-            MinoAggregation::Ss0(m) => self.action(m, event),
-            MinoAggregation::Zs0(m) => self.action(m, event),
-            MinoAggregation::Js0(m) => self.action(m, event),
-            MinoAggregation::Ls0(m) => self.action(m, event),
-            MinoAggregation::Ts0(m) => self.action(m, event),
-            MinoAggregation::IsR(m) => self.action(m, event),
-            MinoAggregation::OsR(m) => self.action(m, event),
-            MinoAggregation::SsR(m) => self.action(m, event),
-            MinoAggregation::ZsR(m) => self.action(m, event),
-            MinoAggregation::JsR(m) => self.action(m, event),
-            MinoAggregation::LsR(m) => self.action(m, event),
-            MinoAggregation::TsR(m) => self.action(m, event),
-            MinoAggregation::Is2(m) => self.action(m, event),
-            MinoAggregation::Os2(m) => self.action(m, event),
-            MinoAggregation::Ss2(m) => self.action(m, event),
-            MinoAggregation::Zs2(m) => self.action(m, event),
-            MinoAggregation::Js2(m) => self.action(m, event),
-            MinoAggregation::Ls2(m) => self.action(m, event),
-            MinoAggregation::Ts2(m) => self.action(m, event),
-            MinoAggregation::IsL(m) => self.action(m, event),
-            MinoAggregation::OsL(m) => self.action(m, event),
-            MinoAggregation::SsL(m) => self.action(m, event),
-            MinoAggregation::ZsL(m) => self.action(m, event),
-            MinoAggregation::JsL(m) => self.action(m, event),
-            MinoAggregation::LsL(m) => self.action(m, event),
-            MinoAggregation::TsL(m) => self.action(m, event),
-        };
+        let mut mino = self.state.mino.take().unwrap();
+        let next = match_mino_state!(&mut mino, m => self.action(m, event));
 
         match next {
-            None => self.mino = Some(mino),
-            Some(new) => self.mino = Some(new),
+            None => self.state.mino = Some(mino),
+            Some(new) => self.state.mino = Some(new),
         };
     }
 
     pub fn action(&mut self, mut mino: &mut impl MinoFn, event: Event) -> Option<MinoAggregation> {
         match event {
             Event::MoveR => {
-                self.try_move(mino, OFFSET_RIGHT);
+                if let Ok(_) = self.move_mino(mino, OFFSET_RIGHT) {
+                    self.state.spun = false;
+                    self.reset_by_moving();
+                }
                 None
             }
             Event::MoveL => {
-                self.try_move(mino, OFFSET_LEFT);
+                if let Ok(_) = self.move_mino(mino, OFFSET_LEFT) {
+                    self.state.spun = false;
+                    self.reset_by_moving();
+                }
                 None
             }
             Event::MoveDown => {
-                if self.is_landing {
+                if self.config.lock_delay.move_down_locks && !self.can_fall(mino) {
                     self.lock(mino)
                 } else {
                     self.action(mino, Event::FreeFall)
                 }
             }
-            Event::Land => {
-                if self.is_landing {
-                    self.lock(mino)
-                } else {
-                    self.land(mino)
-                }
-            }
-            Event::RotateR | Event::RotateL => {
+            Event::Land => self.land(mino),
+            Event::RotateR | Event::RotateL | Event::Rotate180 => {
                 // only for O type mino
                 // to detect T-spin MinoO must always fail to rotate
                 if !mino.is_rotatable() {
                     return None;
                 }
 
-                if event == Event::RotateR {
-                    let (mut right, offsets) = mino.right();
-                    self.try_rotate(right, offsets).ok()
-                } else {
-                    let (mut left, offsets) = mino.left();
-                    self.try_rotate(left, offsets).ok()
+                let from = mino.rotation();
+                match event {
+                    Event::RotateR => self.try_rotate(from, mino.right().0, false).ok(),
+                    Event::RotateL => self.try_rotate(from, mino.left().0, false).ok(),
+                    _ => self.try_rotate(from, mino.side().0, true).ok(),
                 }
             }
-            Event::FreeFall => match self.try_move(mino, OFFSET_DOWN) {
-                Ok(_) => self.reset_previous_state(),
-                Err(_) => self.wait_locking(mino),
-            },
-            Event::TimeGo => self.action(mino, Event::FreeFall),
+            Event::FreeFall => {
+                if let Ok(_) = self.move_mino(mino, OFFSET_DOWN) {
+                    self.reset_by_falling(mino.pos().1);
+                }
+                None
+            }
+            Event::TimeGo => {
+                self.state.frames += 1;
+                let next = self.tick(mino);
+                self.check_finished();
+                next
+            }
+
+            Event::Hold => self.hold_mino(mino),
 
             Event::Nop => None,
 
@@ -406,7 +896,7 @@ impl<F: FnMut(GameEvent)> Game<F> {
                             (true, _, _, _) => None,
                             (_, true, _, _) => Some(mino.left().0.into()),
                             (_, _, true, _) => Some(mino.right().0.into()),
-                            (_, _, _, true) => Some(mino.right().0.right().0.into()),
+                            (_, _, _, true) => Some(mino.side().0.into()),
                             _ => None,
                         }
                     }
@@ -414,7 +904,7 @@ impl<F: FnMut(GameEvent)> Game<F> {
                         match (mino.is_0(), mino.is_r(), mino.is_l(), mino.is_2()) {
                             (true, _, _, _) => Some(mino.right().0.into()),
                             (_, true, _, _) => None,
-                            (_, _, true, _) => Some(mino.right().0.right().0.into()),
+                            (_, _, true, _) => Some(mino.side().0.into()),
                             (_, _, _, true) => Some(mino.left().0.into()),
                             _ => None,
                         }
@@ -422,7 +912,7 @@ impl<F: FnMut(GameEvent)> Game<F> {
                     AbsoluteRotation::StateL => {
                         match (mino.is_0(), mino.is_r(), mino.is_l(), mino.is_2()) {
                             (true, _, _, _) => Some(mino.left().0.into()),
-                            (_, true, _, _) => Some(mino.left().0.left().0.into()),
+                            (_, true, _, _) => Some(mino.side().0.into()),
                             (_, _, true, _) => None,
                             (_, _, _, true) => Some(mino.right().0.into()),
                             _ => None,
@@ -430,7 +920,7 @@ impl<F: FnMut(GameEvent)> Game<F> {
                     }
                     AbsoluteRotation::State2 => {
                         match (mino.is_0(), mino.is_r(), mino.is_l(), mino.is_2()) {
-                            (true, _, _, _) => Some(mino.right().0.right().0.into()),
+                            (true, _, _, _) => Some(mino.side().0.into()),
                             (_, true, _, _) => Some(mino.right().0.into()),
                             (_, _, true, _) => Some(mino.left().0.into()),
                             (_, _, _, true) => None,
@@ -443,6 +933,10 @@ impl<F: FnMut(GameEvent)> Game<F> {
     }
 }
 
+fn piece_of(mino: &impl MinoFn) -> Piece {
+    Piece::new(mino.kind(), mino.rotation(), mino.pos())
+}
+
 struct Offset {
     plus: (i8, i8),
     minus: (i8, i8),
@@ -463,7 +957,7 @@ const OFFSET_DOWN: Offset = Offset {
     minus: (0, -1),
 };
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Event {
     MoveR,
     MoveL,
@@ -472,8 +966,13 @@ pub enum Event {
 
     RotateR,
     RotateL,
+    Rotate180,
 
+    Hold,
+
+    // A frame. Gravity is applied by level.
     TimeGo,
+    // Fall a cell.
     FreeFall,
 
     Nop,
@@ -482,16 +981,64 @@ pub enum Event {
     Test(TestEvent),
 }
 
-pub enum GameEvent<'a> {
+/// Copy of the next queue as many as GameConfig::preview.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct NextMinos {
+    kinds: [MinoKind; MAX_PREVIEW],
+    len: usize,
+}
+
+impl NextMinos {
+    pub fn new(kinds: &[MinoKind]) -> Self {
+        let mut next = Self {
+            kinds: [MinoKind::I; MAX_PREVIEW],
+            len: kinds.len(),
+        };
+        next.kinds[..kinds.len()].copy_from_slice(kinds);
+        next
+    }
+}
+
+impl Deref for NextMinos {
+    type Target = [MinoKind];
+
+    fn deref(&self) -> &[MinoKind] {
+        &self.kinds[..self.len]
+    }
+}
+
+/// Events of the start come in the order of Start, ScoreChange, Next and Spawned.
+/// Events of a lock come in the order of
+/// Locked, ComboChanged, BackToBackChanged, ScoreChange, FinesseFault and Finished.
+/// LinesCleared comes after the line clear delay, followed by Next and Spawned of a new mino.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GameEvent {
     Start,
     ScoreChange(Score),
-    Next(&'a [usize]),
+    Next(NextMinos),
+    Hold(MinoKind),
+    Spawned(Piece),
+    // Moved by an event or gravity
+    Moved(Piece),
+    // Rotated with the index of the kick used
+    Rotated { piece: Piece, kick: usize },
+    // Fell onto the ground, or dropped by Event::Land
+    Landed(Piece),
+    Locked([(i8, i8); 4]),
+    ComboChanged(usize),
+    BackToBackChanged(bool),
+    // A mino is locked with more moves and rotations than the minimum.
+    FinesseFault { inputs: usize, minimum: usize },
+    // Rows deleted at once, indices before deleting
+    LinesCleared(ClearedRows, LineClear),
     ChangeNextMinoAggregation,
     Overflow,
+    // The goal of GameMode is reached.
+    Finished(GameResult),
     Nop,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum TestEvent {
     AbsoluteMovement((i8, i8)),
     AbsoluteRotation(AbsoluteRotation),
@@ -504,7 +1051,7 @@ impl Into<Event> for TestEvent {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum AbsoluteRotation {
     State0,
     StateR,
@@ -529,14 +1076,14 @@ pub mod test_uti {
     // ⬛: locked
     // 　: blank
     pub fn print_field<F: FnMut(GameEvent)>(game: &Game<F>, r: Range<usize>) -> String {
-        let mut minos = vec![vec!["⬜"; FIELD_W]; FIELD_H];
-        mut_with_absolute_cells(&game.mino.unwrap(), |x, y| {
+        let mut minos = vec![vec!["⬜"; game.state.field.width()]; game.state.field.height()];
+        mut_with_absolute_cells(&game.state.mino.unwrap(), |x, y| {
             minos[y as usize][x as usize] = "　"
         });
 
-        game.rows().iter().enumerate().rev().for_each(|(y, row)| {
+        game.rows().enumerate().rev().for_each(|(y, row)| {
             row.iter().enumerate().for_each(|(x, cell)| {
-                if *cell {
+                if cell.is_filled() {
                     minos[y as usize][x as usize] = "⬛";
                 }
             });
@@ -564,6 +1111,10 @@ pub mod test_uti {
         define_macro_state_method!(mino, pos())
     }
 
+    pub fn get_mino_index(mino: &MinoAggregation) -> usize {
+        define_macro_state_method!(mino, kind()).index()
+    }
+
     pub fn mino_is_0(mino: &MinoAggregation) -> bool {
         define_macro_state_method!(mino, is_0())
     }
@@ -587,14 +1138,14 @@ pub const TEST_SEED: [u8; 16] = [5; 16];
 
 #[cfg(test)]
 mod tests {
-    use crate::game::test_uti::{get_mino_pos, print_field};
+    use crate::game::test_uti::{get_mino_index, get_mino_pos, mino_is_0, print_field};
     use crate::TestEvent::AbsoluteMovement;
     use crate::*;
     use std::prelude::v1::*;
 
     #[test]
     fn test_step_i() {
-        let mut game = Game::new(TEST_SEED, |_| {});
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
         game.start();
         {
             game.step(AbsoluteMovement((0, 2)));
@@ -674,6 +1225,1121 @@ mod tests {
             println!("{}", print_field(&game, 0..6));
         }
     }
+
+    #[test]
+    fn test_hold() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        let first = get_mino_index(game.mino());
+        game.step(AbsoluteMovement((4, 5)));
+        game.step(Event::Hold);
+        let second = get_mino_index(game.mino());
+        assert_eq!(first, get_mino_index(game.hold().unwrap()));
+        assert_ne!(first, second);
+        assert_eq!(MINO_FIRST_POSITION, get_mino_pos(game.mino()));
+
+        // only once until locking
+        game.step(Event::Hold);
+        assert_eq!(second, get_mino_index(game.mino()));
+        assert_eq!(first, get_mino_index(game.hold().unwrap()));
+
+        game.step(Event::Land);
+        game.step(Event::Nop);
+        game.step(Event::Nop);
+        let third = get_mino_index(game.mino());
+
+        game.step(AbsoluteRotation::StateR);
+        game.step(AbsoluteMovement((2, 5)));
+        game.step(Event::Hold);
+        assert_eq!(first, get_mino_index(game.mino()));
+        assert_eq!(third, get_mino_index(game.hold().unwrap()));
+        assert_eq!(MINO_FIRST_POSITION, get_mino_pos(game.mino()));
+        assert!(mino_is_0(game.mino()));
+        assert!(mino_is_0(game.hold().unwrap()));
+    }
+
+    #[test]
+    fn test_ghost_cells() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        let bottom = FIELD_H as i8 - 1;
+        let ghost = game.ghost_cells();
+        assert_eq!([(3, bottom), (4, bottom), (5, bottom), (6, bottom)], ghost);
+        assert_eq!(MINO_FIRST_POSITION, get_mino_pos(game.mino()));
+
+        game.step(Event::Land);
+        game.step(Event::Nop);
+        game.step(Event::Nop);
+
+        game.step(AbsoluteRotation::StateR);
+        game.step(AbsoluteMovement((3, 4)));
+        let ghost = game.ghost_cells();
+        assert_eq!((3, 4), get_mino_pos(game.mino()));
+
+        game.step(Event::Land);
+        for (x, y) in ghost.iter() {
+            assert!(game.field().block(*x, *y).is_filled());
+        }
+        assert!(ghost.iter().any(|(_, y)| *y == bottom - 1));
+    }
+
+    fn fill_row_except(game: &mut Game<impl FnMut(GameEvent)>, y: i8, holes: &[i8]) {
+        for x in 0..(game.field().width() as i8) {
+            if !holes.contains(&x) {
+                game.field_mut().set(x, y);
+            }
+        }
+    }
+
+    fn game_with(config: GameConfig) -> Game<impl FnMut(GameEvent)> {
+        let mut game = Game::new(TEST_SEED, config, |_| {}).unwrap();
+        game.start();
+        game
+    }
+
+    fn start_with(game: &mut Game<impl FnMut(GameEvent)>, mino: MinoAggregation, pos: (i8, i8)) {
+        game.state.mino = Some(mino);
+        game.step(AbsoluteMovement(pos));
+    }
+
+    #[test]
+    fn test_t_spin_triple() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        game.field_mut().set(1, 17);
+        fill_row_except(&mut game, 19, &[1]);
+        fill_row_except(&mut game, 20, &[1, 2]);
+        fill_row_except(&mut game, 21, &[1]);
+
+        start_with(&mut game, MINOS_SRC[6], (2, 18));
+        game.step(Event::RotateR);
+        assert_eq!((1, 20), get_mino_pos(game.mino()));
+        assert_eq!(crate::game::T_SPIN_FIN_KICK_INDEX, game.state.kick_index);
+
+        game.step(Event::Land);
+        assert_eq!(1, game.score().t_spin3);
+        assert_eq!(3, game.score().deleted_line);
+    }
+
+    #[test]
+    fn test_t_spin_fin_kick_makes_mini_full() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        // a front corner (2, 21) is empty
+        game.field_mut().set(1, 17);
+        fill_row_except(&mut game, 19, &[1]);
+        fill_row_except(&mut game, 20, &[1, 2]);
+        fill_row_except(&mut game, 21, &[1, 2]);
+
+        start_with(&mut game, MINOS_SRC[6], (2, 18));
+        game.step(Event::RotateR);
+        game.step(Event::Land);
+        assert_eq!(1, game.score().t_spin2);
+        assert_eq!(0, game.score().t_spin_mini2);
+    }
+
+    #[test]
+    fn test_t_spin_mini() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        game.field_mut().set(3, 20);
+        game.field_mut().set(6, 20);
+        fill_row_except(&mut game, 21, &[4, 5, 6]);
+
+        start_with(&mut game, MINOS_SRC[6], (4, 20));
+        game.step(AbsoluteRotation::StateR);
+        game.step(Event::RotateL);
+        assert_eq!((5, 21), get_mino_pos(game.mino()));
+
+        game.step(Event::Land);
+        assert_eq!(1, game.score().t_spin_mini1);
+        assert_eq!(0, game.score().t_spin1);
+    }
+
+    #[test]
+    fn test_no_t_spin_without_mino_t() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        fill_row_except(&mut game, 21, &[1]);
+        fill_row_except(&mut game, 20, &[0, 1]);
+
+        // MinoS kicks into the bottom left hole
+        start_with(&mut game, MINOS_SRC[2], (1, 20));
+        game.step(Event::RotateR);
+        assert_eq!((0, 20), get_mino_pos(game.mino()));
+        game.step(Event::Land);
+
+        let score = game.score();
+        assert_eq!(2, score.deleted_line);
+        assert_eq!(0, score.t_spin2 + score.t_spin_mini2);
+    }
+
+    fn land_and_erase(game: &mut Game<impl FnMut(GameEvent)>) {
+        game.step(Event::Land);
+        game.step(Event::Nop);
+        game.step(Event::Nop);
+    }
+
+    // MinoI at StateR in the right end column
+    fn tetris(game: &mut Game<impl FnMut(GameEvent)>) {
+        for y in 18..22 {
+            fill_row_except(game, y, &[9]);
+        }
+        start_with(game, MINOS_SRC[0], (4, 5));
+        game.step(AbsoluteRotation::StateR);
+        game.step(AbsoluteMovement((8, 5)));
+        land_and_erase(game);
+    }
+
+    #[test]
+    fn test_point() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        game.field_mut().set(9, 10);
+
+        fill_row_except(&mut game, 21, &[0, 1, 2, 3]);
+        start_with(&mut game, MINOS_SRC[0], (1, 5));
+        land_and_erase(&mut game);
+        assert_eq!(100, game.score().point);
+        assert_eq!(1, game.score().combo);
+
+        start_with(&mut game, MINOS_SRC[0], (1, 5));
+        land_and_erase(&mut game);
+        assert_eq!(100, game.score().point);
+        assert_eq!(0, game.score().combo);
+    }
+
+    #[test]
+    fn test_point_back_to_back_and_combo() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        game.field_mut().set(0, 10);
+
+        tetris(&mut game);
+        assert_eq!(800, game.score().point);
+        assert_eq!(0, game.score().back_to_back);
+
+        tetris(&mut game);
+        assert_eq!(800 + 1200 + 50, game.score().point);
+        assert_eq!(1, game.score().back_to_back);
+        assert_eq!(2, game.score().combo);
+        assert_eq!(0, game.score().perfect_clear);
+    }
+
+    #[test]
+    fn test_perfect_clear() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        tetris(&mut game);
+        assert_eq!(800 + 2000, game.score().point);
+        assert_eq!(1, game.score().perfect_clear);
+    }
+
+    #[test]
+    fn test_gravity() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        let (x, y) = MINO_FIRST_POSITION;
+        for _ in 0..59 {
+            game.step(Event::TimeGo);
+        }
+        assert_eq!((x, y), get_mino_pos(game.mino()));
+
+        game.step(Event::TimeGo);
+        assert_eq!((x, y + 1), get_mino_pos(game.mino()));
+    }
+
+    #[test]
+    fn test_gravity_20g_and_locking() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        game.state.score.level = 20;
+
+        game.step(Event::TimeGo);
+        assert_eq!(FIELD_H as i8 - 1, get_mino_pos(game.mino()).1);
+
+        // the landing frame is counted
+        for _ in 1..LOCKING_TIME {
+            game.step(Event::TimeGo);
+        }
+        assert!(!game.state.is_locked);
+
+        game.step(Event::TimeGo);
+        assert!(game.state.is_locked);
+
+        // over the height of the default display
+        let mut game = game_with(GameConfig::with_field_size(FIELD_W, 40));
+        game.state.score.level = 20;
+
+        game.step(Event::TimeGo);
+        assert_eq!(game.config().field_h as i8 - 1, get_mino_pos(game.mino()).1);
+    }
+
+    // Land at once and wait until just before locking.
+    fn wait_until_locking(game: &mut Game<impl FnMut(GameEvent)>) {
+        game.state.score.level = 20;
+        for _ in 0..game.config().lock_delay.frames {
+            game.step(Event::TimeGo);
+        }
+        assert!(!game.state.is_locked);
+    }
+
+    #[test]
+    fn test_lock_delay_move_reset() {
+        let mut game = game_with(GameConfig {
+            lock_delay: LockDelay {
+                reset: LockReset::Move(1),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        wait_until_locking(&mut game);
+
+        game.step(Event::MoveL);
+        for _ in 0..game.config().lock_delay.frames {
+            game.step(Event::TimeGo);
+        }
+        assert!(!game.state.is_locked);
+
+        // over the limit
+        game.step(Event::MoveR);
+        game.step(Event::TimeGo);
+        assert!(game.state.is_locked);
+    }
+
+    #[test]
+    fn test_lock_delay_step_reset() {
+        let mut game = game_with(GameConfig {
+            lock_delay: LockDelay {
+                reset: LockReset::Step,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        wait_until_locking(&mut game);
+
+        game.step(Event::MoveL);
+        game.step(Event::TimeGo);
+        assert!(game.state.is_locked);
+    }
+
+    #[test]
+    fn test_lock_delay_infinity() {
+        let mut game = game_with(GameConfig {
+            lock_delay: LockDelay {
+                reset: LockReset::Infinity,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        wait_until_locking(&mut game);
+
+        for _ in 0..100 {
+            game.step(Event::MoveL);
+            game.step(Event::MoveR);
+            for _ in 0..game.config().lock_delay.frames {
+                game.step(Event::TimeGo);
+            }
+        }
+        assert!(!game.state.is_locked);
+    }
+
+    #[test]
+    fn test_move_down_while_landing() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        game.state.score.level = 20;
+        game.step(Event::TimeGo);
+        game.step(Event::MoveDown);
+        assert!(game.state.is_locked);
+
+        let config = GameConfig {
+            lock_delay: LockDelay {
+                move_down_locks: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut game = game_with(config);
+        game.state.score.level = 20;
+        game.step(Event::TimeGo);
+        game.step(Event::MoveDown);
+        assert!(!game.state.is_locked);
+    }
+
+    #[test]
+    fn test_config_field_size() {
+        let config = GameConfig::with_field_size(6, 10);
+        assert_eq!((2, 1), config.mino_first_position);
+        assert_eq!(12, config.field_h);
+        assert_eq!(FIELD_TOP, config.field_top());
+
+        let mut game = game_with(config);
+
+        for _ in 0..10 {
+            game.step(Event::MoveR);
+        }
+        assert_eq!((3, 1), get_mino_pos(game.mino()));
+
+        let cells = game.ghost_cells();
+        assert_eq!([(2, 11), (3, 11), (4, 11), (5, 11)], cells);
+        assert_eq!(12, game.rows().len());
+        assert!(game.rows().all(|row| row.len() == 6));
+    }
+
+    #[test]
+    fn test_config_validate() {
+        let new = |config: GameConfig| Game::new(TEST_SEED, config, |_| {}).map(|_| ());
+
+        assert_eq!(Ok(()), new(GameConfig::default()));
+        assert_eq!(
+            Err(ConfigError::FieldWidth),
+            new(GameConfig::with_field_size(MIN_FIELD_W - 1, 20))
+        );
+        assert_eq!(
+            Err(ConfigError::FieldWidth),
+            new(GameConfig {
+                field_w: 20,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Err(ConfigError::FieldHeight),
+            new(GameConfig::with_field_size(FIELD_W, MAX_FIELD_H))
+        );
+        assert_eq!(
+            Err(ConfigError::FieldHeight),
+            new(GameConfig {
+                display_field_h: FIELD_H + 1,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            Err(ConfigError::Preview),
+            new(GameConfig {
+                preview: MAX_PREVIEW + 1,
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_narrow_field() {
+        let mut game = Game::new(TEST_SEED, GameConfig::with_field_size(4, 20), |_| {}).unwrap();
+        game.start();
+        assert_eq!((1, 1), get_mino_pos(game.mino()));
+
+        game.step(Event::MoveL);
+        game.step(Event::MoveR);
+        game.step(Event::MoveR);
+        assert_eq!((1, 1), get_mino_pos(game.mino()));
+
+        game.step(Event::Land);
+        assert!(game.field().row(21).iter().all(|b| b.is_filled()));
+        game.step(Event::Nop);
+        game.step(Event::Nop);
+        assert!(game.field().is_perfect_clear());
+        assert_eq!(1, game.score().deleted_line);
+    }
+
+    #[test]
+    fn test_config_tall_field() {
+        let config = GameConfig::with_field_size(FIELD_W, 40);
+        let mut game = game_with(config);
+        assert_eq!(MINO_FIRST_POSITION, get_mino_pos(game.mino()));
+
+        game.step(Event::Land);
+        assert_eq!(42, game.rows().len());
+        assert!(game.field().block(4, 41).is_filled());
+    }
+
+    #[test]
+    fn test_config_spawn_rule() {
+        let config = GameConfig {
+            spawn: SpawnRule {
+                drop_on_spawn: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut game = game_with(config);
+        let (x, y) = MINO_FIRST_POSITION;
+        assert_eq!((x, y + 1), get_mino_pos(game.mino()));
+
+        let config = GameConfig {
+            spawn: SpawnRule {
+                block_out: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut game = game_with(config);
+        game.field_mut().set(4, 1);
+        land_and_erase(&mut game);
+        assert!(!game.state.alive);
+    }
+
+    #[test]
+    fn test_config_preview() {
+        for size in [0, 5, MAX_PREVIEW].iter() {
+            let mut preview = vec![];
+            {
+                let config = GameConfig {
+                    preview: *size,
+                    ..Default::default()
+                };
+                let mut game = Game::new(TEST_SEED, config, |e| {
+                    if let GameEvent::Next(next) = e {
+                        preview = next.to_vec();
+                    }
+                })
+                .unwrap();
+                game.start();
+                assert_eq!(*size, game.next_queue().len());
+
+                // the queue goes ahead by a mino
+                let queue = game.next_queue().to_vec();
+                game.step(Event::Hold);
+                if *size != 0 {
+                    assert_eq!(queue[0], game.mino().kind());
+                    assert_eq!(queue[1..], game.next_queue()[..*size - 1]);
+                }
+            }
+            assert_eq!(*size, preview.len());
+        }
+    }
+
+    #[test]
+    fn test_lines_cleared() {
+        let mut cleared = None;
+        {
+            let mut game = Game::new(TEST_SEED, GameConfig::default(), |e| {
+                if let GameEvent::LinesCleared(rows, _) = e {
+                    cleared = Some(rows);
+                }
+            })
+            .unwrap();
+            game.start();
+            for y in [18, 19, 21].iter() {
+                fill_row_except(&mut game, *y, &[9]);
+            }
+            fill_row_except(&mut game, 20, &[8, 9]);
+            game.field_mut().set(0, 16);
+
+            start_with(&mut game, MINOS_SRC[0], (4, 5));
+            game.step(AbsoluteRotation::StateR);
+            game.step(AbsoluteMovement((8, 5)));
+            land_and_erase(&mut game);
+
+            assert_eq!(3, game.score().deleted_line);
+            assert_eq!(Block::Mino(MinoKind::I), game.field().block(9, 21));
+            assert_eq!(Block::Empty, game.field().block(8, 21));
+            assert_eq!(Block::Garbage, game.field().block(0, 19));
+        }
+        let cleared = cleared.unwrap();
+        assert_eq!(vec![18, 19, 21], cleared.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_config_randomizer() {
+        let mut next = vec![];
+        {
+            let config = GameConfig {
+                randomizer: RandomizerRule::Sequence(Sequence::parse("TIO").unwrap()),
+                ..Default::default()
+            };
+            let mut game = Game::new(TEST_SEED, config, |e| {
+                if let GameEvent::Next(n) = e {
+                    next = n.to_vec();
+                }
+            })
+            .unwrap();
+            game.start();
+            assert_eq!(MinoKind::T, game.mino().kind());
+        }
+        assert_eq!(vec![MinoKind::I, MinoKind::O, MinoKind::T], next);
+    }
+
+    #[test]
+    fn test_level_up() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        game.field_mut().set(0, 5);
+        assert_eq!(1, game.score().level);
+
+        tetris(&mut game);
+        tetris(&mut game);
+        assert_eq!(1, game.score().level);
+
+        tetris(&mut game);
+        assert_eq!(2, game.score().level);
+        assert_eq!(800 + 1250 + 1300, game.score().point);
+    }
+
+    #[test]
+    fn test_moving_cancels_spin() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        start_with(&mut game, MINOS_SRC[6], (4, 10));
+        game.step(Event::RotateR);
+        assert!(game.state.spun);
+
+        game.step(Event::MoveL);
+        assert!(!game.state.spun);
+    }
+
+    #[test]
+    fn test_rotate_180() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+
+        start_with(&mut game, MINOS_SRC[6], (4, 10));
+        game.step(Event::Rotate180);
+        assert_eq!(Rotation::State2, game.mino().rotation());
+        assert_eq!((4, 10), game.mino().pos());
+
+        game.step(Event::Rotate180);
+        game.field_mut().set(4, 11);
+        game.step(Event::Rotate180);
+        assert_eq!(Rotation::State2, game.mino().rotation());
+        assert_eq!((4, 9), game.mino().pos());
+        assert_eq!(1, game.state.kick_index);
+    }
+
+    #[test]
+    fn test_t_spin_by_rotate_180() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        fill_row_except(&mut game, 21, &[4]);
+        fill_row_except(&mut game, 20, &[3, 4, 5]);
+        game.field_mut().set(3, 19);
+
+        start_with(&mut game, MINOS_SRC[6], (4, 20));
+        game.step(Event::Rotate180);
+        assert!(game.state.spun);
+
+        land_and_erase(&mut game);
+        assert_eq!(1, game.score().t_spin2);
+    }
+
+    #[test]
+    fn test_rotate_180_has_no_fin_kick() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        game.field_mut().set(3, 15);
+        game.field_mut().set(5, 14);
+        game.field_mut().set(5, 12);
+        game.field_mut().set(3, 14);
+
+        start_with(&mut game, MINOS_SRC[6], (4, 15));
+        game.step(AbsoluteRotation::StateR);
+        game.step(Event::Rotate180);
+        assert_eq!(Rotation::StateL, game.mino().rotation());
+        assert_eq!((4, 13), game.mino().pos());
+        assert_eq!(crate::game::T_SPIN_FIN_KICK_INDEX, game.state.kick_index);
+
+        // the front corner (3, 12) is empty
+        game.step(Event::Land);
+        assert_eq!(0, game.score().t_spin_zero);
+        assert_eq!(1, game.score().t_spin_mini_zero);
+    }
+
+    #[test]
+    fn test_generate_moves() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        fill_row_except(&mut game, 21, &[4]);
+        fill_row_except(&mut game, 20, &[3, 4, 5]);
+        game.field_mut().set(3, 19);
+        start_with(&mut game, MINOS_SRC[6], MINO_FIRST_POSITION);
+
+        let mut generator = Box::new(MoveGenerator::new());
+        game.generate_moves(&mut generator, MoveOptions::default());
+        let slot = Piece::new(MinoKind::T, Rotation::State2, (4, 20));
+        let placement = generator.placements().find(|p| p.piece == slot).unwrap();
+
+        for event in generator.path(&placement).unwrap().events() {
+            game.step(*event);
+        }
+        game.step(Event::Nop);
+        game.step(Event::Nop);
+        assert_eq!(1, game.score().t_spin2);
+    }
+
+    #[test]
+    fn test_find_path() {
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
+        game.start();
+        start_with(&mut game, MINOS_SRC[6], MINO_FIRST_POSITION);
+
+        let mut generator = Box::new(MoveGenerator::new());
+        let options = MoveOptions {
+            rotate_180: false,
+            ..MoveOptions::default()
+        };
+        let target = Piece::new(MinoKind::T, Rotation::StateL, (1, 20));
+        let path = game.find_path(&mut generator, target, options).unwrap();
+        assert_eq!(5, path.len());
+
+        for event in path.events() {
+            game.step(*event);
+        }
+        for (x, y) in target.cells().iter() {
+            assert!(game.field().test(*x, *y));
+        }
+
+        // floating
+        let target = Piece::new(MinoKind::T, Rotation::StateL, (1, 10));
+        assert_eq!(
+            Err(PathError::Unreachable),
+            game.find_path(&mut generator, target, options)
+        );
+    }
+
+    #[test]
+    fn test_find_path_lock_resets() {
+        let mut generator = Box::new(MoveGenerator::new());
+        let target = Piece::new(MinoKind::O, Rotation::State0, (0, 20));
+        let game_on_roof = |frames: u8, roof: &[i8]| {
+            let mut game = game_with(GameConfig {
+                lock_delay: LockDelay {
+                    frames,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            for x in roof.iter() {
+                game.field_mut().set(*x, 7);
+            }
+            start_with(&mut game, MINOS_SRC[MinoKind::O.index()], (4, 5));
+            game
+        };
+
+        // moving left on the roof is the shortest
+        let game = game_on_roof(LOCKING_TIME, &[2, 3, 4]);
+        let path = game.find_path(&mut generator, target, MoveOptions::default());
+        assert_eq!(5, path.unwrap().len());
+
+        // leaving the roof to the right and going under it
+        let mut game = game_on_roof(0, &[2, 3, 4]);
+        let path = game.find_path(&mut generator, target, MoveOptions::default());
+        let path = path.unwrap();
+        assert_eq!(10, path.len());
+
+        for event in path.events() {
+            game.step(*event);
+            if *event != Event::Land {
+                game.step(Event::TimeGo);
+            }
+        }
+        for (x, y) in target.cells().iter() {
+            assert!(game.field().test(*x, *y));
+        }
+
+        // no way but on the roof
+        let on_roof = Piece::new(MinoKind::O, Rotation::State0, (0, 5));
+        let game = game_on_roof(0, &[0, 1, 2, 3, 4]);
+        let path = game.find_path(&mut generator, on_roof, MoveOptions::default());
+        assert_eq!(Err(PathError::LockResets), path);
+    }
+
+    #[test]
+    fn test_finesse_fault() {
+        let mut faults = vec![];
+        {
+            let config = GameConfig {
+                randomizer: RandomizerRule::Sequence(Sequence::parse("T").unwrap()),
+                ..Default::default()
+            };
+            let mut game = Game::new(TEST_SEED, config, |e| {
+                if let GameEvent::FinesseFault { inputs, minimum } = e {
+                    faults.push((inputs, minimum));
+                }
+            })
+            .unwrap();
+            game.start();
+
+            for event in [Event::MoveL, Event::MoveR, Event::MoveL].iter() {
+                game.step(*event);
+            }
+            land_and_erase(&mut game);
+            assert_eq!(1, game.score().finesse_fault);
+
+            // just enough
+            game.step(Event::Rotate180);
+            game.step(Event::MoveR);
+            land_and_erase(&mut game);
+            assert_eq!(1, game.score().finesse_fault);
+
+            // inputs before hold are not counted
+            game.step(Event::RotateR);
+            game.step(Event::RotateL);
+            game.step(Event::Hold);
+            land_and_erase(&mut game);
+            assert_eq!(1, game.score().finesse_fault);
+        }
+        assert_eq!(vec![(3, 1)], faults);
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let mut events = vec![];
+        {
+            let config = GameConfig {
+                randomizer: RandomizerRule::Sequence(Sequence::parse("I").unwrap()),
+                ..Default::default()
+            };
+            let mut game = Game::new(TEST_SEED, config, |e| {
+                events.push(match e {
+                    GameEvent::Start => "start",
+                    GameEvent::ScoreChange(_) => "score",
+                    GameEvent::Next(_) => "next",
+                    GameEvent::Spawned(_) => "spawned",
+                    GameEvent::Moved(piece) => {
+                        assert_eq!((3, 1), piece.pos());
+                        "moved"
+                    }
+                    GameEvent::Rotated { piece, kick } => {
+                        assert_eq!(Rotation::StateR, piece.rotation);
+                        assert_eq!(0, kick);
+                        "rotated"
+                    }
+                    GameEvent::Landed(_) => "landed",
+                    GameEvent::Locked(cells) => {
+                        assert_eq!([(9, 21), (9, 20), (9, 19), (9, 18)], cells);
+                        "locked"
+                    }
+                    GameEvent::ComboChanged(combo) => {
+                        assert_eq!(1, combo);
+                        "combo"
+                    }
+                    GameEvent::BackToBackChanged(back_to_back) => {
+                        assert!(back_to_back);
+                        "b2b"
+                    }
+                    GameEvent::LinesCleared(_, clear) => {
+                        assert_eq!(4, clear.lines);
+                        assert_eq!(TSpin::None, clear.t_spin);
+                        "cleared"
+                    }
+                    _ => "other",
+                })
+            })
+            .unwrap();
+            game.start();
+            game.step(Event::MoveL);
+            game.step(Event::RotateR);
+            tetris(&mut game);
+        }
+
+        let expected = vec![
+            "start", "score", "next", "spawned", "moved", "rotated", "landed", "locked", "combo",
+            "b2b", "score", "cleared", "next", "spawned",
+        ];
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn test_poll_events() {
+        let mut game = Game::with_queue(TEST_SEED, GameConfig::default()).unwrap();
+        game.start();
+        let events = game.drain_events().collect::<Vec<_>>();
+        assert_eq!(Some(&GameEvent::Start), events.first());
+        let spawned = Piece::from(*game.mino());
+        assert_eq!(Some(&GameEvent::Spawned(spawned)), events.last());
+        assert!(events.iter().any(|e| match e {
+            GameEvent::Next(next) => next.len() == 3,
+            _ => false,
+        }));
+        assert_eq!(None, game.poll_event());
+
+        game.step(Event::MoveL);
+        let moved = Piece::from(*game.mino());
+        assert_eq!(Some(GameEvent::Moved(moved)), game.poll_event());
+        assert_eq!(None, game.poll_event());
+    }
+
+    #[test]
+    fn test_poll_events_20g() {
+        // 20G informs a move for each row of the tallest field
+        let hidden = FIELD_H - DISPLAY_FIELD_H;
+        let config = GameConfig::with_field_size(FIELD_W, MAX_FIELD_H - hidden);
+        let mut game = Game::with_queue(TEST_SEED, config).unwrap();
+        game.start();
+        game.state.score.level = 20;
+
+        let bottom = config.field_h as i8;
+        for y in bottom - 4..bottom {
+            for x in 0..9 {
+                game.field_mut().set(x, y);
+            }
+        }
+        game.state.mino = Some(MINOS_SRC[0]);
+        game.step(AbsoluteRotation::StateR);
+        game.step(AbsoluteMovement((8, 5)));
+
+        let mut most = 0;
+        for _ in 0..(LOCKING_TIME + LOCKING_WAIT_TIME) as usize + 4 {
+            game.step(Event::TimeGo);
+            most = most.max(game.drain_events().count());
+        }
+        assert_eq!(1, game.score().tetris);
+        assert!(MAX_FIELD_H - 8 < most && most <= EVENT_QUEUE_CAPACITY);
+        assert_eq!(0, game.dropped_events());
+
+        // without polling
+        game.state.score.level = 20;
+        for _ in 0..(LOCKING_TIME + LOCKING_WAIT_TIME) as usize * 2 {
+            game.step(Event::TimeGo);
+        }
+        assert!(game.dropped_events() > 0);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let config = GameConfig {
+            randomizer: RandomizerRule::Random(PureRandom),
+            ..Default::default()
+        };
+        let mut game = game_with(config);
+        game.step(Event::MoveL);
+
+        let play = |game: &mut Game<_>| {
+            for event in [
+                Event::RotateR,
+                Event::TimeGo,
+                Event::Land,
+                Event::Nop,
+                Event::Nop,
+            ]
+            .iter()
+            .cycle()
+            .take(20)
+            {
+                game.step(*event);
+            }
+        };
+
+        let snapshot = game.snapshot();
+        play(&mut game);
+        let played = game.snapshot();
+        assert_ne!(snapshot, played);
+
+        game.restore(snapshot);
+        assert_eq!(&snapshot, game.state());
+        play(&mut game);
+        assert_eq!(played, game.snapshot());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_save_and_load() {
+        let config = GameConfig {
+            randomizer: RandomizerRule::Random(PureRandom),
+            ..Default::default()
+        };
+        let mut game = Game::with_queue(TEST_SEED, config).unwrap();
+        game.start();
+        for event in [
+            Event::MoveL,
+            Event::Land,
+            Event::Nop,
+            Event::Nop,
+            Event::RotateR,
+        ]
+        .iter()
+        {
+            game.step(*event);
+        }
+
+        let saved = serde_json::to_string(game.state()).unwrap();
+        let mut loaded = Game::with_queue([0; 16], config).unwrap();
+        loaded.restore(serde_json::from_str(&saved).unwrap());
+        assert_eq!(game.state(), loaded.state());
+
+        for _ in 0..10 {
+            for event in [Event::TimeGo, Event::Land, Event::Nop, Event::Nop].iter() {
+                game.step(*event);
+                loaded.step(*event);
+            }
+        }
+        assert_eq!(game.state(), loaded.state());
+        assert_eq!(game.next_queue(), loaded.next_queue());
+    }
+
+    #[test]
+    fn test_mode_sprint() {
+        let mut result = None;
+        {
+            let config = GameConfig {
+                mode: GameMode::Sprint { lines: 4 },
+                ..Default::default()
+            };
+            let mut game = Game::new(TEST_SEED, config, |e| {
+                if let GameEvent::Finished(r) = e {
+                    result = Some(r);
+                }
+            })
+            .unwrap();
+            game.start();
+            game.step(Event::TimeGo);
+            game.step(Event::TimeGo);
+            tetris(&mut game);
+            assert!(!game.is_alive());
+
+            // no more steps after finished
+            game.step(Event::TimeGo);
+            assert_eq!(2, game.frames());
+        }
+        let result = result.unwrap();
+        assert_eq!(2, result.frames);
+        assert_eq!(1, result.score.tetris);
+    }
+
+    #[test]
+    fn test_mode_ultra() {
+        let mut game = game_with(GameConfig {
+            mode: GameMode::Ultra { frames: 3 },
+            ..Default::default()
+        });
+        game.step(Event::TimeGo);
+        game.step(Event::TimeGo);
+        assert!(game.is_alive());
+        game.step(Event::TimeGo);
+        assert!(!game.is_alive());
+
+        let mut game = game_with(GameConfig {
+            mode: GameMode::Endless,
+            ..Default::default()
+        });
+        for _ in 0..ULTRA_FRAMES {
+            game.step(Event::Nop);
+        }
+        assert!(game.is_alive());
+    }
+
+    #[test]
+    fn test_mode_zen() {
+        // locked above the display
+        let mut game = game_with(GameConfig {
+            mode: GameMode::Zen,
+            ..Default::default()
+        });
+        for y in 2..22 {
+            game.field_mut().set(4, y);
+        }
+        start_with(&mut game, MINOS_SRC[1], (4, 0));
+        land_and_erase(&mut game);
+        assert!(game.is_alive());
+
+        let mut game = game_with(GameConfig {
+            mode: GameMode::Endless,
+            ..Default::default()
+        });
+        for y in 2..22 {
+            game.field_mut().set(4, y);
+        }
+        start_with(&mut game, MINOS_SRC[1], (4, 0));
+        land_and_erase(&mut game);
+        assert!(!game.is_alive());
+
+        // blocked at the spawn even without SpawnRule::block_out
+        let mut game = game_with(GameConfig {
+            mode: GameMode::Zen,
+            ..Default::default()
+        });
+        assert!(!game.config().spawn.block_out);
+        for y in 0..4 {
+            game.field_mut().set(4, y);
+        }
+        assert!(game.new_mino().is_some());
+        assert!(game.is_alive());
+        assert_eq!(&Field::with_size(FIELD_W, FIELD_H), game.field());
+    }
+
+    #[test]
+    fn test_rotation_i_kicks() {
+        let expected = [
+            (RotationRule::Srs, (2, 10)),
+            (RotationRule::SrsPlus, (5, 10)),
+            (RotationRule::NoKick, (4, 10)),
+        ];
+
+        for (rotation, pos) in expected.iter() {
+            let mut game = game_with(GameConfig {
+                rotation: *rotation,
+                ..Default::default()
+            });
+            game.field_mut().set(5, 11);
+            start_with(&mut game, MINOS_SRC[0], (4, 10));
+
+            game.step(Event::RotateR);
+            assert_eq!(*pos, get_mino_pos(game.mino()), "{:?}", rotation);
+            let rotated = game.mino().rotation() == Rotation::StateR;
+            assert_eq!(*rotation != RotationRule::NoKick, rotated);
+        }
+    }
+
+    #[test]
+    fn test_rotation_ars() {
+        let mut game = game_with(GameConfig {
+            rotation: RotationRule::Ars,
+            ..Default::default()
+        });
+        let t = game.spawn(MINOS_SRC[6]).unwrap();
+        assert_eq!(Rotation::State2, t.rotation());
+        assert_eq!(MINO_FIRST_POSITION, t.pos());
+
+        // pointing up keeps the bottom row
+        game.state.mino = Some(t);
+        game.step(Event::RotateR);
+        game.step(Event::RotateR);
+        assert_eq!(Rotation::State0, game.mino().rotation());
+        assert_eq!((4, 2), game.mino().pos());
+
+        // center column rule
+        start_with(&mut game, t, (4, 10));
+        game.field_mut().set(4, 9);
+        game.step(Event::RotateR);
+        assert_eq!(Rotation::State2, game.mino().rotation());
+
+        game.field_mut().set_block(4, 9, Block::Empty);
+        game.field_mut().set(3, 10);
+        game.step(Event::RotateR);
+        assert_eq!(Rotation::StateL, game.mino().rotation());
+        assert_eq!((5, 10), game.mino().pos());
+    }
+
+    #[test]
+    fn test_rotation_nrs() {
+        let mut game = game_with(GameConfig {
+            rotation: RotationRule::Nrs,
+            ..Default::default()
+        });
+        let s = game.spawn(MINOS_SRC[2]).unwrap();
+        assert_eq!(Rotation::State2, s.rotation());
+
+        // two states lean right
+        start_with(&mut game, s, (4, 10));
+        game.step(Event::RotateR);
+        let mut cells = Piece::from(*game.mino()).cells();
+        cells.sort();
+        assert_eq!([(4, 9), (4, 10), (5, 10), (5, 11)], cells);
+
+        game.step(Event::RotateL);
+        game.step(Event::RotateL);
+        assert_eq!(Rotation::StateR, game.mino().rotation());
+        assert_eq!((4, 10), game.mino().pos());
+    }
 }
 
 #[cfg(test)]
@@ -750,7 +2416,7 @@ mod only_test_method_tests {
 
     #[test]
     fn test_absolute_rotation() {
-        let mut game = Game::new(TEST_SEED, |_| {});
+        let mut game = Game::new(TEST_SEED, GameConfig::default(), |_| {}).unwrap();
         game.start();
         let mut mino = MINOS_SRC[0];
 
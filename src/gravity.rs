@@ -0,0 +1,48 @@
+use crate::*;
+
+/// Gravity is cells per frame in 1 / GRAVITY_UNIT.
+pub const GRAVITY_UNIT: u32 = 1 << 16;
+
+/// Fall through the whole display in a frame.
+/// Game moves a mino straight to the bottom from this, even on a taller field.
+pub const GRAVITY_20G: u32 = DISPLAY_FIELD_H as u32 * GRAVITY_UNIT;
+
+pub const LINES_PER_LEVEL: usize = 10;
+
+// Level 1 to 18 from the guideline formula, (0.8 - (level - 1) * 0.007) ^ (level - 1) seconds per row
+// in 60 frames per second. Over level 18 is 20G.
+const GRAVITY_TABLE: [u32; 18] = [
+    1093, 1378, 1769, 2311, 3076, 4169, 5759, 8107, 11635, 17027, 25416, 38709, 60169, 95484,
+    154743, 256187, 433425, 749597,
+];
+
+pub fn gravity(level: usize) -> u32 {
+    match level {
+        0 => GRAVITY_TABLE[0],
+        level if level <= GRAVITY_TABLE.len() => GRAVITY_TABLE[level - 1],
+        _ => GRAVITY_20G,
+    }
+}
+
+pub fn level_by_lines(deleted_line: usize) -> usize {
+    deleted_line / LINES_PER_LEVEL + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_gravity() {
+        // a cell per 60 frames
+        assert!(gravity(1) * 59 < GRAVITY_UNIT);
+        assert!(gravity(1) * 60 >= GRAVITY_UNIT);
+
+        for level in 1..30 {
+            assert!(gravity(level) <= gravity(level + 1));
+        }
+
+        assert_eq!(GRAVITY_20G, gravity(19));
+        assert_eq!(GRAVITY_20G, gravity(100));
+    }
+}
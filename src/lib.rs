@@ -18,13 +18,38 @@ extern crate std;
 #[macro_export]
 mod macros;
 
+#[cfg(feature = "serde")]
+mod big_array;
+
+mod config;
+mod event_sink;
 mod field;
+mod finesse;
 mod game;
+mod gravity;
+mod lock_delay;
 mod mino;
+mod mode;
+mod movegen;
+mod randomizer;
+mod replay;
+mod rng;
+mod rotation_system;
 
+pub use config::*;
+pub use event_sink::*;
 pub use field::*;
+pub use finesse::*;
 pub use game::*;
+pub use gravity::*;
+pub use lock_delay::*;
 pub use mino::*;
+pub use mode::*;
+pub use movegen::*;
+pub use randomizer::*;
+pub use replay::*;
+pub use rng::*;
+pub use rotation_system::*;
 
 pub const FIELD_W: usize = 10;
 pub const DISPLAY_FIELD_H: usize = 20;
@@ -34,8 +59,18 @@ pub const DISPLAY_FIELD_H: usize = 20;
 // 2 is top of display.
 pub const FIELD_H: usize = DISPLAY_FIELD_H + 2;
 
+// Capacity of Field. Any size up to these can be chosen per game.
+// MinoI needs MIN_FIELD_W to spawn.
+pub const MIN_FIELD_W: usize = 4;
+pub const MAX_FIELD_W: usize = 16;
+pub const MAX_FIELD_H: usize = 48;
+
+// Capacity of the next queue. Up to this can be previewed.
+pub const MAX_PREVIEW: usize = 14;
+
 pub const MINO_FIRST_POSITION: (i8, i8) = (4, 1);
 pub const FIELD_TOP: i8 = 2;
 
-pub const LOCKING_TIME: u8 = 2;
+// frames
+pub const LOCKING_TIME: u8 = 30;
 pub const LOCKING_WAIT_TIME: u8 = 1;
@@ -0,0 +1,95 @@
+use crate::*;
+
+/// The guideline limits resets to 15 times.
+pub const LOCK_RESET_LIMIT: u8 = 15;
+
+/// How moving or rotating on the ground postpones locking.
+/// Falling to a lower row than ever always resets the delay.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum LockReset {
+    /// Moving or rotating resets the delay up to the count in the lowest row.
+    Move(u8),
+    /// Only falling resets the delay.
+    Step,
+    /// Moving or rotating always resets the delay.
+    Infinity,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LockDelay {
+    /// Frames from landing to locking.
+    pub frames: u8,
+    pub reset: LockReset,
+    /// Event::MoveDown locks the mino at once while landing.
+    pub move_down_locks: bool,
+}
+
+impl Default for LockDelay {
+    fn default() -> Self {
+        Self {
+            frames: LOCKING_TIME,
+            reset: LockReset::Move(LOCK_RESET_LIMIT),
+            move_down_locks: true,
+        }
+    }
+}
+
+/// Lock delay of the current mino.
+/// Game and the path search of MoveGenerator follow the rule by this together.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct LockProgress {
+    // Frames while the mino is on the ground.
+    landing: u8,
+    // Count of resets by moving or rotating.
+    resets: u8,
+    // The lowest row the mino has reached.
+    lowest_y: i8,
+}
+
+impl LockProgress {
+    pub(crate) const fn new(y: i8) -> Self {
+        Self {
+            landing: 0,
+            resets: 0,
+            lowest_y: y,
+        }
+    }
+
+    /// Count a frame on the ground. Return true when the mino locks.
+    pub(crate) fn wait(&mut self, delay: &LockDelay) -> bool {
+        self.landing = self.landing.saturating_add(1);
+        self.landing > delay.frames
+    }
+
+    pub(crate) fn fall(&mut self, y: i8) {
+        if y > self.lowest_y {
+            *self = Self::new(y);
+        }
+    }
+
+    pub(crate) fn reset_by_moving(&mut self, delay: &LockDelay) {
+        // the delay has not started
+        if self.landing == 0 {
+            return;
+        }
+
+        match delay.reset {
+            LockReset::Move(limit) => {
+                if self.resets < limit {
+                    self.resets += 1;
+                    self.landing = 0;
+                }
+            }
+            LockReset::Step => {}
+            LockReset::Infinity => self.landing = 0,
+        }
+    }
+
+    /// Not later than the other in every count, so that it locks no earlier.
+    pub(crate) fn is_ahead_of(&self, other: &Self) -> bool {
+        self.landing <= other.landing
+            && self.resets <= other.resets
+            && self.lowest_y <= other.lowest_y
+    }
+}
@@ -33,3 +33,39 @@ macro_rules! define_macro_state_method {
         }
     };
 }
+
+#[macro_export]
+macro_rules! match_mino_state {
+    ( $mino:expr, $m:ident => $body:expr ) => {
+        match $mino {
+            MinoAggregation::Is0($m) => $body,
+            MinoAggregation::Os0($m) => $body,
+            MinoAggregation::Ss0($m) => $body,
+            MinoAggregation::Zs0($m) => $body,
+            MinoAggregation::Js0($m) => $body,
+            MinoAggregation::Ls0($m) => $body,
+            MinoAggregation::Ts0($m) => $body,
+            MinoAggregation::IsR($m) => $body,
+            MinoAggregation::OsR($m) => $body,
+            MinoAggregation::SsR($m) => $body,
+            MinoAggregation::ZsR($m) => $body,
+            MinoAggregation::JsR($m) => $body,
+            MinoAggregation::LsR($m) => $body,
+            MinoAggregation::TsR($m) => $body,
+            MinoAggregation::Is2($m) => $body,
+            MinoAggregation::Os2($m) => $body,
+            MinoAggregation::Ss2($m) => $body,
+            MinoAggregation::Zs2($m) => $body,
+            MinoAggregation::Js2($m) => $body,
+            MinoAggregation::Ls2($m) => $body,
+            MinoAggregation::Ts2($m) => $body,
+            MinoAggregation::IsL($m) => $body,
+            MinoAggregation::OsL($m) => $body,
+            MinoAggregation::SsL($m) => $body,
+            MinoAggregation::ZsL($m) => $body,
+            MinoAggregation::JsL($m) => $body,
+            MinoAggregation::LsL($m) => $body,
+            MinoAggregation::TsL($m) => $body,
+        }
+    };
+}
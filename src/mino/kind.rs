@@ -0,0 +1,67 @@
+use crate::*;
+
+/// Runtime mark of MinoType.
+/// The order is the same as MINOS_SRC.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MinoKind {
+    I,
+    O,
+    S,
+    Z,
+    J,
+    L,
+    T,
+}
+
+impl MinoKind {
+    /// In the order of MINOS_SRC.
+    pub const ALL: [MinoKind; 7] = [
+        MinoKind::I,
+        MinoKind::O,
+        MinoKind::S,
+        MinoKind::Z,
+        MinoKind::J,
+        MinoKind::L,
+        MinoKind::T,
+    ];
+
+    /// Index of MINOS_SRC
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Panic if the index is out of MINOS_SRC.
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL[index]
+    }
+
+    /// Accept a letter of the kind in upper or lower case.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'I' => Some(MinoKind::I),
+            'O' => Some(MinoKind::O),
+            'S' => Some(MinoKind::S),
+            'Z' => Some(MinoKind::Z),
+            'J' => Some(MinoKind::J),
+            'L' => Some(MinoKind::L),
+            'T' => Some(MinoKind::T),
+            _ => None,
+        }
+    }
+}
+
+pub trait Kind {
+    fn kind(&self) -> MinoKind;
+    fn rotation(&self) -> Rotation;
+}
+
+impl<MT: MinoType, Rot: RotationState> Kind for MinoState<MT, Rot> {
+    fn kind(&self) -> MinoKind {
+        MT::kind()
+    }
+
+    fn rotation(&self) -> Rotation {
+        Rot::rotation()
+    }
+}
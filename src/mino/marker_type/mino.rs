@@ -2,12 +2,21 @@ use crate::*;
 
 define_markers!(MinoI, MinoO, MinoS, MinoZ, MinoJ, MinoL, MinoT);
 
-pub trait MinoType: NewMarker {}
+pub trait MinoType: NewMarker {
+    fn kind() -> MinoKind;
+}
 
-impl MinoType for MinoI {}
-impl MinoType for MinoO {}
-impl MinoType for MinoS {}
-impl MinoType for MinoZ {}
-impl MinoType for MinoJ {}
-impl MinoType for MinoL {}
-impl MinoType for MinoT {}
+#[rustfmt::skip]
+impl MinoType for MinoI { fn kind() -> MinoKind { MinoKind::I } }
+#[rustfmt::skip]
+impl MinoType for MinoO { fn kind() -> MinoKind { MinoKind::O } }
+#[rustfmt::skip]
+impl MinoType for MinoS { fn kind() -> MinoKind { MinoKind::S } }
+#[rustfmt::skip]
+impl MinoType for MinoZ { fn kind() -> MinoKind { MinoKind::Z } }
+#[rustfmt::skip]
+impl MinoType for MinoJ { fn kind() -> MinoKind { MinoKind::J } }
+#[rustfmt::skip]
+impl MinoType for MinoL { fn kind() -> MinoKind { MinoKind::L } }
+#[rustfmt::skip]
+impl MinoType for MinoT { fn kind() -> MinoKind { MinoKind::T } }
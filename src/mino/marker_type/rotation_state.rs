@@ -2,9 +2,25 @@ use crate::*;
 
 define_markers!(State0, StateR, StateL, State2);
 
-pub trait RotationState: NewMarker {}
+pub trait RotationState: NewMarker {
+    fn rotation() -> Rotation;
+}
 
-impl RotationState for State0 {}
-impl RotationState for StateR {}
-impl RotationState for StateL {}
-impl RotationState for State2 {}
+#[rustfmt::skip]
+impl RotationState for State0 { fn rotation() -> Rotation { Rotation::State0 } }
+#[rustfmt::skip]
+impl RotationState for StateR { fn rotation() -> Rotation { Rotation::StateR } }
+#[rustfmt::skip]
+impl RotationState for StateL { fn rotation() -> Rotation { Rotation::StateL } }
+#[rustfmt::skip]
+impl RotationState for State2 { fn rotation() -> Rotation { Rotation::State2 } }
+
+/// Runtime mark of RotationState.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rotation {
+    State0,
+    StateR,
+    State2,
+    StateL,
+}
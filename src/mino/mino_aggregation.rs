@@ -44,3 +44,67 @@ macro_rules! define_first_minos {
         ];
   }
 }
+
+macro_rules! new_mino_state {
+    ( $mino_type:tt, $rotation:expr, $pos:expr ) => {
+        match $rotation {
+            Rotation::State0 => MinoState::<$mino_type, State0>::new_with($pos).into(),
+            Rotation::StateR => MinoState::<$mino_type, StateR>::new_with($pos).into(),
+            Rotation::State2 => MinoState::<$mino_type, State2>::new_with($pos).into(),
+            Rotation::StateL => MinoState::<$mino_type, StateL>::new_with($pos).into(),
+        }
+    };
+}
+
+/// Equal when the kind, the rotation and the position are equal.
+impl PartialEq for MinoAggregation {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind() == other.kind()
+            && self.rotation() == other.rotation()
+            && self.pos() == other.pos()
+    }
+}
+
+impl Eq for MinoAggregation {}
+
+/// Saved as Piece.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MinoAggregation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Piece::from(*self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MinoAggregation {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Piece::deserialize(deserializer).map(Into::into)
+    }
+}
+
+impl MinoAggregation {
+    /// Build a mino from runtime marks.
+    pub fn new(kind: MinoKind, rotation: Rotation, pos: (i8, i8)) -> Self {
+        match kind {
+            MinoKind::I => new_mino_state!(MinoI, rotation, pos),
+            MinoKind::O => new_mino_state!(MinoO, rotation, pos),
+            MinoKind::S => new_mino_state!(MinoS, rotation, pos),
+            MinoKind::Z => new_mino_state!(MinoZ, rotation, pos),
+            MinoKind::J => new_mino_state!(MinoJ, rotation, pos),
+            MinoKind::L => new_mino_state!(MinoL, rotation, pos),
+            MinoKind::T => new_mino_state!(MinoT, rotation, pos),
+        }
+    }
+
+    pub fn kind(&self) -> MinoKind {
+        define_macro_state_method!(self, kind())
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        define_macro_state_method!(self, rotation())
+    }
+
+    pub fn pos(&self) -> (i8, i8) {
+        define_macro_state_method!(self, pos())
+    }
+}
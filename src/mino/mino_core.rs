@@ -23,12 +23,20 @@ impl<MT: MinoType, Rot: RotationState> MinoState<MT, Rot> {
 use crate::*;
 
 pub trait MinoFn:
-    NewWith + Right + Left + WithCell + Rotatable + IsState + Into<MinoAggregation>
+    NewWith + Right + Left + Side + WithCell + Rotatable + IsState + Kind + Into<MinoAggregation>
 {
 }
 
-impl<T: NewWith + Right + Left + WithCell + Rotatable + IsState + Into<MinoAggregation>> MinoFn
-    for T
+impl<T> MinoFn for T where
+    T: NewWith
+        + Right
+        + Left
+        + Side
+        + WithCell
+        + Rotatable
+        + IsState
+        + Kind
+        + Into<MinoAggregation>
 {
 }
 
@@ -83,6 +91,10 @@ macro_rules! define_mino {
         define_rotation!(Left, $mino_type, $mino_form, StateL => State2);
         define_rotation!(Left, $mino_type, $mino_form, State2 => StateR);
         define_rotation!(Left, $mino_type, $mino_form, StateR => State0);
+        define_rotation!(Side, $mino_type, $mino_form, State0 => State2);
+        define_rotation!(Side, $mino_type, $mino_form, State2 => State0);
+        define_rotation!(Side, $mino_type, $mino_form, StateR => StateL);
+        define_rotation!(Side, $mino_type, $mino_form, StateL => StateR);
     };
 }
 
@@ -2,10 +2,12 @@ mod rotation;
 
 mod cells;
 mod is_state;
+mod kind;
 mod marker_type;
 mod mino_aggregation;
 mod mino_core;
 mod new_with;
+mod piece;
 mod position;
 mod rotatable;
 mod srs_offset;
@@ -13,10 +15,12 @@ mod with_cells;
 
 pub use cells::*;
 pub use is_state::*;
+pub use kind::*;
 pub use marker_type::*;
 pub use mino_aggregation::*;
 pub use mino_core::*;
 pub use new_with::*;
+pub use piece::*;
 pub use position::*;
 pub use rotatable::*;
 pub use rotation::*;
@@ -0,0 +1,89 @@
+use crate::*;
+
+/// Plain value of a mino.
+/// Converts to and from MinoAggregation without loss.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Piece {
+    pub kind: MinoKind,
+    pub rotation: Rotation,
+    pub x: i8,
+    pub y: i8,
+}
+
+impl Piece {
+    pub fn new(kind: MinoKind, rotation: Rotation, (x, y): (i8, i8)) -> Self {
+        Self {
+            kind,
+            rotation,
+            x,
+            y,
+        }
+    }
+
+    pub fn kind(&self) -> MinoKind {
+        self.kind
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    pub fn pos(&self) -> (i8, i8) {
+        (self.x, self.y)
+    }
+
+    /// Cells relative to the position.
+    pub fn shape(&self) -> &'static [(i8, i8)] {
+        fn shape_of<M: Cell>(_: &M) -> &'static [(i8, i8)] {
+            M::cells()
+        }
+
+        let mino: MinoAggregation = (*self).into();
+        match_mino_state!(&mino, m => shape_of(m))
+    }
+
+    /// Cells in the field.
+    pub fn cells(&self) -> [(i8, i8); 4] {
+        let mino: MinoAggregation = (*self).into();
+        match_mino_state!(&mino, m => m.absolute_cells())
+    }
+}
+
+impl From<MinoAggregation> for Piece {
+    fn from(mino: MinoAggregation) -> Self {
+        Piece::new(mino.kind(), mino.rotation(), mino.pos())
+    }
+}
+
+impl From<Piece> for MinoAggregation {
+    fn from(piece: Piece) -> Self {
+        MinoAggregation::new(piece.kind, piece.rotation, piece.pos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_conversion() {
+        let rotations = [
+            Rotation::State0,
+            Rotation::StateR,
+            Rotation::State2,
+            Rotation::StateL,
+        ];
+
+        for kind in MinoKind::ALL.iter() {
+            for rotation in rotations.iter() {
+                let piece = Piece::new(*kind, *rotation, (3, 5));
+                let mino: MinoAggregation = piece.into();
+                assert_eq!(piece, Piece::from(mino));
+            }
+        }
+
+        let piece = Piece::new(MinoKind::T, Rotation::StateR, (3, 5));
+        assert_eq!([(3, 6), (3, 5), (4, 5), (3, 4)], piece.cells());
+    }
+}
@@ -24,6 +24,18 @@ pub trait Right: MinoCore + Position {
     }
 }
 
+pub trait Side: MinoCore + Position {
+    type Next: MinoFn<Now = Self::Side, Right = Self::Left, Side = Self::Now, Left = Self::Right>;
+    type Srs: SrsOffsetExe<Form = Self::Form, Now = Self::Now, Next = Self::Side>;
+
+    fn side(&self) -> (Self::Next, &[(i8, i8)]) {
+        let next = Self::Next::new_with(self.pos());
+        let srs = Self::Srs::offset();
+
+        (next, srs)
+    }
+}
+
 #[macro_export]
 macro_rules! define_rotation {
     ( $direction:tt, $mino_type:tt, $mino_form:tt, $from:tt => $to:tt ) => {
@@ -5,6 +5,9 @@ pub struct SrsOffset<T: MinoForm, A: RotationState, B: RotationState>(T, A, B);
 /// Return offsets list to try searching valid space after failure of rotation.
 /// (This challenge is called "Super Rotation System")
 ///
+/// y grows downward as same as Field, so the table is upside down of the guideline's one.
+/// The guideline has no 180 rotation, so the tables of TETR.IO are used for it.
+///
 /// Associated types are just for binding types in other trait definition.
 pub trait SrsOffsetExe {
     type Form: MinoForm;
@@ -29,20 +32,88 @@ macro_rules! define_srs_offset {
     };
 }
 
-define_srs_offset!(BarTypeMino, State0 => StateR, [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]);
-define_srs_offset!(BarTypeMino, StateR => State0, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]);
-define_srs_offset!(BarTypeMino, StateR => State2, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]);
-define_srs_offset!(BarTypeMino, State2 => StateR, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]);
-define_srs_offset!(BarTypeMino, State2 => StateL, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]);
-define_srs_offset!(BarTypeMino, StateL => State2, [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]);
-define_srs_offset!(BarTypeMino, StateL => State0, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]);
-define_srs_offset!(BarTypeMino, State0 => StateL, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]);
-
-define_srs_offset!(NormalTypeMino, State0 => StateR, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]);
-define_srs_offset!(NormalTypeMino, StateR => State0, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]);
-define_srs_offset!(NormalTypeMino, StateR => State2, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]);
-define_srs_offset!(NormalTypeMino, State2 => StateR, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]);
-define_srs_offset!(NormalTypeMino, State2 => StateL, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]);
-define_srs_offset!(NormalTypeMino, StateL => State2, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]);
-define_srs_offset!(NormalTypeMino, StateL => State0, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]);
-define_srs_offset!(NormalTypeMino, State0 => StateL, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]);
+define_srs_offset!(BarTypeMino, State0 => StateR, [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]);
+define_srs_offset!(BarTypeMino, StateR => State0, [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)]);
+define_srs_offset!(BarTypeMino, StateR => State2, [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]);
+define_srs_offset!(BarTypeMino, State2 => StateR, [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)]);
+define_srs_offset!(BarTypeMino, State2 => StateL, [(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)]);
+define_srs_offset!(BarTypeMino, StateL => State2, [(0, 0), (-2, 0), (1, 0), (-2, 1), (1, -2)]);
+define_srs_offset!(BarTypeMino, StateL => State0, [(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)]);
+define_srs_offset!(BarTypeMino, State0 => StateL, [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]);
+
+define_srs_offset!(BarTypeMino, State0 => State2, [(0, 0), (0, -1), (1, -1), (-1, -1), (1, 0), (-1, 0)]);
+define_srs_offset!(BarTypeMino, State2 => State0, [(0, 0), (0, 1), (-1, 1), (1, 1), (-1, 0), (1, 0)]);
+define_srs_offset!(BarTypeMino, StateR => StateL, [(0, 0), (1, 0), (1, -2), (1, -1), (0, -2), (0, -1)]);
+define_srs_offset!(BarTypeMino, StateL => StateR, [(0, 0), (-1, 0), (-1, -2), (-1, -1), (0, -2), (0, -1)]);
+
+define_srs_offset!(NormalTypeMino, State0 => StateR, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]);
+define_srs_offset!(NormalTypeMino, StateR => State0, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]);
+define_srs_offset!(NormalTypeMino, StateR => State2, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]);
+define_srs_offset!(NormalTypeMino, State2 => StateR, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]);
+define_srs_offset!(NormalTypeMino, State2 => StateL, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]);
+define_srs_offset!(NormalTypeMino, StateL => State2, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]);
+define_srs_offset!(NormalTypeMino, StateL => State0, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]);
+define_srs_offset!(NormalTypeMino, State0 => StateL, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]);
+
+define_srs_offset!(NormalTypeMino, State0 => State2, [(0, 0), (0, -1), (1, -1), (-1, -1), (1, 0), (-1, 0)]);
+define_srs_offset!(NormalTypeMino, State2 => State0, [(0, 0), (0, 1), (-1, 1), (1, 1), (-1, 0), (1, 0)]);
+define_srs_offset!(NormalTypeMino, StateR => StateL, [(0, 0), (1, 0), (1, -2), (1, -1), (0, -2), (0, -1)]);
+define_srs_offset!(NormalTypeMino, StateL => StateR, [(0, 0), (-1, 0), (-1, -2), (-1, -1), (0, -2), (0, -1)]);
+
+macro_rules! lookup_srs_offset {
+    ( $form:tt, $from:expr, $to:expr ) => {
+        match ($from, $to) {
+            (Rotation::State0, Rotation::StateR) => SrsOffset::<$form, State0, StateR>::offset(),
+            (Rotation::StateR, Rotation::State0) => SrsOffset::<$form, StateR, State0>::offset(),
+            (Rotation::StateR, Rotation::State2) => SrsOffset::<$form, StateR, State2>::offset(),
+            (Rotation::State2, Rotation::StateR) => SrsOffset::<$form, State2, StateR>::offset(),
+            (Rotation::State2, Rotation::StateL) => SrsOffset::<$form, State2, StateL>::offset(),
+            (Rotation::StateL, Rotation::State2) => SrsOffset::<$form, StateL, State2>::offset(),
+            (Rotation::StateL, Rotation::State0) => SrsOffset::<$form, StateL, State0>::offset(),
+            (Rotation::State0, Rotation::StateL) => SrsOffset::<$form, State0, StateL>::offset(),
+            (Rotation::State0, Rotation::State2) => SrsOffset::<$form, State0, State2>::offset(),
+            (Rotation::State2, Rotation::State0) => SrsOffset::<$form, State2, State0>::offset(),
+            (Rotation::StateR, Rotation::StateL) => SrsOffset::<$form, StateR, StateL>::offset(),
+            (Rotation::StateL, Rotation::StateR) => SrsOffset::<$form, StateL, StateR>::offset(),
+            _ => &[(0, 0)],
+        }
+    };
+}
+
+/// Runtime lookup of the tables above.
+pub fn srs_offset(kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+    match kind {
+        MinoKind::I => lookup_srs_offset!(BarTypeMino, from, to),
+        _ => lookup_srs_offset!(NormalTypeMino, from, to),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::prelude::v1::*;
+
+    // Guideline tables are written with y upward.
+    fn flip(kicks: &[(i8, i8)]) -> Vec<(i8, i8)> {
+        kicks.iter().map(|(x, y)| (*x, -y)).collect()
+    }
+
+    #[test]
+    fn test_guideline_kicks() {
+        // J, L, S, T and Z from spawn to right: the third kick goes up a row.
+        assert_eq!(
+            flip(&[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+            SrsOffset::<NormalTypeMino, State0, StateR>::offset()
+        );
+        assert_eq!(
+            flip(&[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+            SrsOffset::<NormalTypeMino, StateR, State0>::offset()
+        );
+
+        // I from spawn to right
+        assert_eq!(
+            flip(&[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]),
+            SrsOffset::<BarTypeMino, State0, StateR>::offset()
+        );
+    }
+}
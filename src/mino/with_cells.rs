@@ -27,6 +27,22 @@ pub trait WithCell: Position + Cell {
 
         false
     }
+
+    /// Cells in the field.
+    fn absolute_cells(&self) -> [(i8, i8); 4] {
+        let mut cells = [(0, 0); 4];
+        let mut i = 0;
+        self.mut_with_absolute_cells(|x, y| {
+            cells[i] = (x, y);
+            i += 1;
+        });
+        cells
+    }
+
+    /// For hit testing by row masks of the field.
+    fn test_with_field(&self, field: &Field) -> bool {
+        field.test_cells(self.pos(), Self::cells())
+    }
 }
 
 impl<T: Position + Cell> WithCell for T {}
@@ -0,0 +1,70 @@
+use crate::*;
+
+pub const MARATHON_LINES: usize = 150;
+pub const SPRINT_LINES: usize = 40;
+// 2 minutes at 60 frames per second
+pub const ULTRA_FRAMES: u32 = 2 * 60 * 60;
+
+/// Goal and end conditions of a game.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GameMode {
+    /// Play until top out.
+    Endless,
+    /// Finish when the lines are cleared.
+    Marathon { lines: usize },
+    /// Finish when the lines are cleared, for the time in frames.
+    Sprint { lines: usize },
+    /// Finish when the frames pass, for the score.
+    Ultra { frames: u32 },
+    /// Never top out. The field is cleared instead.
+    Zen,
+}
+
+impl Default for GameMode {
+    fn default() -> Self {
+        GameMode::Endless
+    }
+}
+
+impl GameMode {
+    pub fn marathon() -> Self {
+        GameMode::Marathon {
+            lines: MARATHON_LINES,
+        }
+    }
+
+    pub fn sprint() -> Self {
+        GameMode::Sprint {
+            lines: SPRINT_LINES,
+        }
+    }
+
+    pub fn ultra() -> Self {
+        GameMode::Ultra {
+            frames: ULTRA_FRAMES,
+        }
+    }
+
+    pub fn tops_out(&self) -> bool {
+        *self != GameMode::Zen
+    }
+
+    pub fn is_finished(&self, frames: u32, score: &Score) -> bool {
+        match *self {
+            GameMode::Marathon { lines } | GameMode::Sprint { lines } => {
+                score.deleted_line >= lines
+            }
+            GameMode::Ultra { frames: limit } => frames >= limit,
+            GameMode::Endless | GameMode::Zen => false,
+        }
+    }
+}
+
+/// Carried by GameEvent::Finished when the goal of the mode is reached.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GameResult {
+    pub mode: GameMode,
+    // Event::TimeGo from the start
+    pub frames: u32,
+    pub score: Score,
+}
@@ -0,0 +1,469 @@
+use crate::*;
+
+// Every position where a mino can fit, with margins for cells out of the position.
+const X_OFFSET: i8 = 3;
+const X_RANGE: usize = MAX_FIELD_W + 6;
+const Y_OFFSET: i8 = 2;
+const Y_RANGE: usize = MAX_FIELD_H + 4;
+const NODE_COUNT: usize = X_RANGE * Y_RANGE * 4 * 2;
+
+const UNVISITED: u16 = u16::MAX;
+
+pub const MAX_PATH_LEN: usize = 128;
+
+const ROTATIONS: [Rotation; 4] = [
+    Rotation::State0,
+    Rotation::StateR,
+    Rotation::State2,
+    Rotation::StateL,
+];
+
+/// Inputs used in the search.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MoveOptions {
+    pub rotate_180: bool,
+    pub soft_drop: bool,
+}
+
+impl Default for MoveOptions {
+    fn default() -> Self {
+        Self {
+            rotate_180: true,
+            soft_drop: true,
+        }
+    }
+}
+
+/// A final resting state of a mino.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Placement {
+    pub piece: Piece,
+    // the last move was a rotation
+    pub spun: bool,
+    node: u16,
+}
+
+/// Events to reach a placement, ending with Event::Land.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Path {
+    events: [Event; MAX_PATH_LEN],
+    len: usize,
+}
+
+impl Path {
+    pub fn events(&self) -> &[Event] {
+        &self.events[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Why Game::find_path gives no path.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PathError {
+    // The target is not a resting placement reachable from the mino.
+    Unreachable,
+    // Every way locks the mino on the way by the lock delay and the reset rule.
+    LockResets,
+}
+
+#[derive(Copy, Clone)]
+struct Node {
+    // the previous node and the event from it
+    parent: u16,
+    event: Event,
+    // the node which lands on this placement first
+    landed_from: u16,
+    // the lock delay when reached, only followed by Game::find_path
+    lock: LockProgress,
+    queued: bool,
+}
+
+const EMPTY_NODE: Node = Node {
+    parent: UNVISITED,
+    event: Event::Nop,
+    landed_from: UNVISITED,
+    lock: LockProgress::new(0),
+    queued: false,
+};
+
+/// Breadth first search of every placement reachable from a mino
+/// by moves, rotations with kicks and soft drops.
+///
+/// Buffers are kept to reuse for each search.
+pub struct MoveGenerator {
+    kind: MinoKind,
+    nodes: [Node; NODE_COUNT],
+    queue: [u16; NODE_COUNT],
+    placements: [u16; NODE_COUNT],
+    placement_count: usize,
+}
+
+impl Default for MoveGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveGenerator {
+    pub fn new() -> Self {
+        Self {
+            kind: MinoKind::I,
+            nodes: [EMPTY_NODE; NODE_COUNT],
+            queue: [0; NODE_COUNT],
+            placements: [0; NODE_COUNT],
+            placement_count: 0,
+        }
+    }
+
+    /// Search from the piece. Return the count of placements.
+    pub fn generate<S: RotationSystem + ?Sized>(
+        &mut self,
+        field: &Field,
+        system: &S,
+        piece: Piece,
+        options: MoveOptions,
+    ) -> usize {
+        self.search(field, system, piece, options, None)
+    }
+
+    /// Search only the ways which keep the mino from locking by the lock delay,
+    /// with a frame after each event.
+    /// A node is visited again when it is reached with a later lock, even by a longer way.
+    pub(crate) fn generate_locking<S: RotationSystem + ?Sized>(
+        &mut self,
+        field: &Field,
+        system: &S,
+        piece: Piece,
+        options: MoveOptions,
+        delay: LockDelay,
+        lock: LockProgress,
+    ) -> usize {
+        self.search(field, system, piece, options, Some((delay, lock)))
+    }
+
+    fn search<S: RotationSystem + ?Sized>(
+        &mut self,
+        field: &Field,
+        system: &S,
+        piece: Piece,
+        options: MoveOptions,
+        locking: Option<(LockDelay, LockProgress)>,
+    ) -> usize {
+        self.kind = piece.kind;
+        self.nodes = [EMPTY_NODE; NODE_COUNT];
+        self.placement_count = 0;
+
+        if !fits(field, piece) {
+            return 0;
+        }
+
+        let start = node_index(piece, false);
+        self.nodes[start as usize].parent = start;
+        if let Some((_, lock)) = locking {
+            self.nodes[start as usize].lock = lock;
+        }
+        self.queue[0] = start;
+        // the queue is a ring, where a node is queued once at a time
+        let (mut head, mut tail) = (0, 1);
+
+        while head < tail {
+            let index = self.queue[head % NODE_COUNT];
+            head += 1;
+            self.nodes[index as usize].queued = false;
+
+            let (piece, spun) = self.node(index);
+
+            // keep the spin only when the mino does not fall
+            let landed = drop_to_bottom(field, piece);
+            let landed = node_index(landed, spun && landed.y == piece.y);
+            if self.nodes[landed as usize].landed_from == UNVISITED {
+                self.nodes[landed as usize].landed_from = index;
+                self.placements[self.placement_count] = landed;
+                self.placement_count += 1;
+            }
+
+            let events = [
+                Event::MoveL,
+                Event::MoveR,
+                Event::RotateR,
+                Event::RotateL,
+                Event::Rotate180,
+                Event::MoveDown,
+            ];
+            for event in events.iter() {
+                let next = match event {
+                    Event::MoveL => shift(field, piece, (-1, 0)).map(|p| (p, false)),
+                    Event::MoveR => shift(field, piece, (1, 0)).map(|p| (p, false)),
+                    // only while the mino can fall, so that it never locks by MoveDown
+                    Event::MoveDown if options.soft_drop => {
+                        shift(field, piece, (0, 1)).map(|p| (p, false))
+                    }
+                    Event::Rotate180 if !options.rotate_180 => None,
+                    Event::RotateR | Event::RotateL | Event::Rotate180 => {
+                        rotate(field, system, piece, *event).map(|p| (p, true))
+                    }
+                    _ => None,
+                };
+
+                let (next, spun) = match next {
+                    Some(next) => next,
+                    None => continue,
+                };
+
+                let mut lock = self.nodes[index as usize].lock;
+                if let Some((delay, _)) = locking {
+                    match event {
+                        Event::MoveDown => lock.fall(next.y),
+                        Event::MoveL | Event::MoveR => lock.reset_by_moving(&delay),
+                        _ => {
+                            lock.reset_by_moving(&delay);
+                            lock.fall(next.y);
+                        }
+                    }
+
+                    // locked on the way
+                    if shift(field, next, (0, 1)).is_none() && lock.wait(&delay) {
+                        continue;
+                    }
+                }
+
+                let next = node_index(next, spun);
+                let node = &mut self.nodes[next as usize];
+                if node.parent != UNVISITED {
+                    let later = locking.is_some()
+                        && next != start
+                        && lock != node.lock
+                        && lock.is_ahead_of(&node.lock);
+                    if !later {
+                        continue;
+                    }
+                }
+
+                node.parent = index;
+                node.event = *event;
+                node.lock = lock;
+                if !node.queued {
+                    node.queued = true;
+                    self.queue[tail % NODE_COUNT] = next;
+                    tail += 1;
+                }
+            }
+        }
+
+        self.placement_count
+    }
+
+    /// Placements of the last search, in the order of found.
+    pub fn placements(&self) -> impl Iterator<Item = Placement> + '_ {
+        self.placements[..self.placement_count]
+            .iter()
+            .map(move |index| {
+                let (piece, spun) = self.node(*index);
+                Placement {
+                    piece,
+                    spun,
+                    node: *index,
+                }
+            })
+    }
+
+    /// One of the shortest paths to the placement of the last search.
+    /// None if the path is longer than MAX_PATH_LEN.
+    pub fn path(&self, placement: &Placement) -> Option<Path> {
+        let mut path = Path {
+            events: [Event::Nop; MAX_PATH_LEN],
+            len: 0,
+        };
+
+        let mut index = self.nodes[placement.node as usize].landed_from;
+        if index == UNVISITED {
+            return None;
+        }
+
+        path.events[0] = Event::Land;
+        path.len = 1;
+        while self.nodes[index as usize].parent != index {
+            if path.len == MAX_PATH_LEN {
+                return None;
+            }
+            path.events[path.len] = self.nodes[index as usize].event;
+            path.len += 1;
+            index = self.nodes[index as usize].parent;
+        }
+
+        path.events[..path.len].reverse();
+        Some(path)
+    }
+
+    /// The shortest path to the resting piece of the last search.
+    /// None if the piece is not reachable or does not rest there.
+    pub fn path_to(&self, target: Piece) -> Option<Path> {
+        self.placements()
+            .filter(|p| p.piece == target)
+            .filter_map(|p| self.path(&p))
+            .min_by_key(|path| path.len())
+    }
+
+    fn node(&self, index: u16) -> (Piece, bool) {
+        let index = index as usize;
+        let spun = index % 2 == 1;
+        let index = index / 2;
+        let x = (index % X_RANGE) as i8 - X_OFFSET;
+        let index = index / X_RANGE;
+        let y = (index % Y_RANGE) as i8 - Y_OFFSET;
+        let rotation = ROTATIONS[index / Y_RANGE];
+
+        (Piece::new(self.kind, rotation, (x, y)), spun)
+    }
+}
+
+fn node_index(piece: Piece, spun: bool) -> u16 {
+    let x = (piece.x + X_OFFSET) as usize;
+    let y = (piece.y + Y_OFFSET) as usize;
+    let rotation = piece.rotation as usize;
+
+    (((rotation * Y_RANGE + y) * X_RANGE + x) * 2 + spun as usize) as u16
+}
+
+pub(crate) fn fits(field: &Field, piece: Piece) -> bool {
+    !field.test_cells(piece.pos(), piece.shape())
+}
+
+pub(crate) fn shift(field: &Field, mut piece: Piece, (x, y): (i8, i8)) -> Option<Piece> {
+    piece.x += x;
+    piece.y += y;
+    if fits(field, piece) {
+        Some(piece)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn drop_to_bottom(field: &Field, mut piece: Piece) -> Piece {
+    while let Some(next) = shift(field, piece, (0, 1)) {
+        piece = next;
+    }
+    piece
+}
+
+pub(crate) fn rotate<S: RotationSystem + ?Sized>(
+    field: &Field,
+    system: &S,
+    piece: Piece,
+    event: Event,
+) -> Option<Piece> {
+    let mino: MinoAggregation = piece.into();
+    match_mino_state!(&mino, m => rotate_mino(field, system, m, event))
+}
+
+fn rotate_mino<S: RotationSystem + ?Sized>(
+    field: &Field,
+    system: &S,
+    mino: &impl MinoFn,
+    event: Event,
+) -> Option<Piece> {
+    // MinoO never rotates as Game
+    if !mino.is_rotatable() {
+        return None;
+    }
+
+    let from = mino.rotation();
+    match event {
+        Event::RotateR => kick_to_piece(field, system, from, mino.right().0),
+        Event::RotateL => kick_to_piece(field, system, from, mino.left().0),
+        _ => kick_to_piece(field, system, from, mino.side().0),
+    }
+}
+
+fn kick_to_piece<S: RotationSystem + ?Sized>(
+    field: &Field,
+    system: &S,
+    from: Rotation,
+    mut rotated: impl MinoFn,
+) -> Option<Piece> {
+    kick(system, field, from, &mut rotated)?;
+    let mino: MinoAggregation = rotated.into();
+    Some(mino.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::prelude::v1::*;
+
+    fn generator() -> Box<MoveGenerator> {
+        Box::new(MoveGenerator::new())
+    }
+
+    fn spawn(kind: MinoKind) -> Piece {
+        Piece::new(kind, Rotation::State0, MINO_FIRST_POSITION)
+    }
+
+    #[test]
+    fn test_empty_field() {
+        let field = Field::new();
+        let mut generator = generator();
+        let options = MoveOptions::default();
+
+        // 8 + 9 + 8 + 9 columns for each rotation
+        generator.generate(&field, &Srs, spawn(MinoKind::T), options);
+        assert_eq!(34, generator.placements().filter(|p| !p.spun).count());
+
+        // MinoO never spins
+        let count = generator.generate(&field, &Srs, spawn(MinoKind::O), options);
+        assert_eq!(9, count);
+        assert!(generator.placements().all(|p| !p.spun));
+
+        // every path ends with Event::Land at the placement
+        generator.generate(&field, &Srs, spawn(MinoKind::L), options);
+        for placement in generator.placements() {
+            let path = generator.path(&placement).unwrap();
+            assert_eq!(Some(&Event::Land), path.events().last());
+        }
+    }
+
+    #[test]
+    fn test_kick_only_slot() {
+        let mut field = Field::new();
+        for x in 0..10 {
+            if x != 4 {
+                field.set(x, 21);
+            }
+            if x < 3 || 5 < x {
+                field.set(x, 20);
+            }
+        }
+        field.set(3, 19);
+
+        let mut generator = generator();
+        let slot = Piece::new(MinoKind::T, Rotation::State2, (4, 20));
+        let find = |generator: &MoveGenerator| generator.placements().find(|p| p.piece == slot);
+
+        generator.generate(&field, &Srs, spawn(MinoKind::T), MoveOptions::default());
+        let placement = find(&generator).unwrap();
+        assert!(placement.spun);
+
+        let path = generator.path(&placement).unwrap();
+        let events = path.events();
+        assert_eq!(Event::Land, events[events.len() - 1]);
+        assert!(matches!(
+            events[events.len() - 2],
+            Event::RotateR | Event::RotateL | Event::Rotate180
+        ));
+
+        // no way to turn in the slot without soft drop
+        let options = MoveOptions {
+            soft_drop: false,
+            ..MoveOptions::default()
+        };
+        generator.generate(&field, &Srs, spawn(MinoKind::T), options);
+        assert!(find(&generator).is_none());
+    }
+}
@@ -0,0 +1,626 @@
+use crate::*;
+use core::fmt;
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+/// Generator of the mino order.
+/// All randomness comes from the given rng, so the order is fixed by the seed of Game.
+pub trait Randomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind;
+}
+
+/// Shuffled bags which have every kind in the same count.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SavedBag", try_from = "SavedBag")
+)]
+pub struct Bag {
+    // 1 for 7-bag, 2 for 14-bag
+    copies: usize,
+    kinds: [MinoKind; 14],
+    // The bag is refilled when it reaches 7 * copies.
+    position: usize,
+}
+
+impl Bag {
+    /// copies must be 1 or 2.
+    pub fn new(copies: usize) -> Self {
+        assert!(Self::is_valid_copies(copies));
+
+        let mut kinds = [MinoKind::I; 14];
+        for (i, kind) in kinds.iter_mut().enumerate() {
+            *kind = MinoKind::from_index(i % 7);
+        }
+
+        Self {
+            copies,
+            kinds,
+            position: 7 * copies,
+        }
+    }
+
+    pub fn seven() -> Self {
+        Self::new(1)
+    }
+
+    pub fn fourteen() -> Self {
+        Self::new(2)
+    }
+
+    pub fn copies(&self) -> usize {
+        self.copies
+    }
+
+    fn is_valid_copies(copies: usize) -> bool {
+        copies == 1 || copies == 2
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedBag {
+    copies: usize,
+    kinds: [MinoKind; 14],
+    position: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<Bag> for SavedBag {
+    fn from(bag: Bag) -> Self {
+        Self {
+            copies: bag.copies,
+            kinds: bag.kinds,
+            position: bag.position,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl core::convert::TryFrom<SavedBag> for Bag {
+    type Error = &'static str;
+
+    fn try_from(saved: SavedBag) -> Result<Self, Self::Error> {
+        if !Bag::is_valid_copies(saved.copies) {
+            return Err("bag copies must be 1 or 2");
+        }
+
+        let size = 7 * saved.copies;
+        if saved.position > size {
+            return Err("bag position out of the bag");
+        }
+
+        let bag = &saved.kinds[..size];
+        if MinoKind::ALL
+            .iter()
+            .any(|kind| bag.iter().filter(|k| *k == kind).count() != saved.copies)
+        {
+            return Err("bag lacks some kinds");
+        }
+
+        Ok(Self {
+            copies: saved.copies,
+            kinds: saved.kinds,
+            position: saved.position,
+        })
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind {
+        let size = 7 * self.copies;
+        if self.position == size {
+            self.kinds[..size].shuffle(rng);
+            self.position = 0;
+        }
+
+        self.position += 1;
+        self.kinds[self.position - 1]
+    }
+}
+
+/// Every kind in the same chance each time.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PureRandom;
+
+impl Randomizer for PureRandom {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind {
+        MinoKind::from_index(rng.gen_range(0, 7))
+    }
+}
+
+/// NES Tetris rolls once again when it hits the last kind or the spare 8th number.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NesRandom {
+    last: Option<MinoKind>,
+}
+
+impl Randomizer for NesRandom {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind {
+        let index = rng.gen_range(0, 8);
+        let kind = if index == 7 || self.last.map(|k| k.index()) == Some(index) {
+            MinoKind::from_index(rng.gen_range(0, 7))
+        } else {
+            MinoKind::from_index(index)
+        };
+
+        self.last = Some(kind);
+        kind
+    }
+}
+
+pub const TGM_ROLLS: u8 = 4;
+pub const TGM2_ROLLS: u8 = 6;
+
+/// TGM rolls up to the count while the kind is in the last 4 kinds.
+/// The history starts with Z, Z, Z, Z as TGM, or Z, S, S, Z as TGM2 from TGM2_ROLLS.
+/// The first kind is never S, Z or O.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SavedTgmHistory", try_from = "SavedTgmHistory")
+)]
+pub struct TgmHistory {
+    rolls: u8,
+    history: [MinoKind; 4],
+    first: bool,
+}
+
+impl TgmHistory {
+    pub fn new(rolls: u8) -> Self {
+        use MinoKind::*;
+
+        Self {
+            rolls,
+            history: if rolls < TGM2_ROLLS {
+                [Z, Z, Z, Z]
+            } else {
+                [Z, S, S, Z]
+            },
+            first: true,
+        }
+    }
+
+    pub fn tgm() -> Self {
+        Self::new(TGM_ROLLS)
+    }
+
+    pub fn tgm2() -> Self {
+        Self::new(TGM2_ROLLS)
+    }
+
+    pub fn rolls(&self) -> u8 {
+        self.rolls
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedTgmHistory {
+    rolls: u8,
+    history: [MinoKind; 4],
+    first: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<TgmHistory> for SavedTgmHistory {
+    fn from(tgm: TgmHistory) -> Self {
+        Self {
+            rolls: tgm.rolls,
+            history: tgm.history,
+            first: tgm.first,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl core::convert::TryFrom<SavedTgmHistory> for TgmHistory {
+    type Error = &'static str;
+
+    fn try_from(saved: SavedTgmHistory) -> Result<Self, Self::Error> {
+        // The history moves only after the first kind.
+        if saved.first && saved.history != TgmHistory::new(saved.rolls).history {
+            return Err("tgm history changed before the first kind");
+        }
+
+        Ok(Self {
+            rolls: saved.rolls,
+            history: saved.history,
+            first: saved.first,
+        })
+    }
+}
+
+impl Randomizer for TgmHistory {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind {
+        let kind = if self.first {
+            self.first = false;
+            *[MinoKind::I, MinoKind::J, MinoKind::L, MinoKind::T]
+                .choose(rng)
+                .unwrap()
+        } else {
+            let mut kind = MinoKind::I;
+            for _ in 0..self.rolls.max(1) {
+                kind = MinoKind::from_index(rng.gen_range(0, 7));
+                if !self.history.contains(&kind) {
+                    break;
+                }
+            }
+            kind
+        };
+
+        self.history.rotate_right(1);
+        self.history[0] = kind;
+        kind
+    }
+}
+
+pub const MAX_SEQUENCE_LEN: usize = 64;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SequenceError {
+    Empty,
+    TooLong,
+    UnknownKind(char),
+}
+
+/// Repeat a fixed order.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "SavedSequence", try_from = "SavedSequence")
+)]
+pub struct Sequence {
+    #[cfg_attr(feature = "serde", serde(with = "crate::big_array"))]
+    kinds: [MinoKind; MAX_SEQUENCE_LEN],
+    len: usize,
+    position: usize,
+}
+
+impl Sequence {
+    pub fn new(kinds: &[MinoKind]) -> Result<Self, SequenceError> {
+        if kinds.is_empty() {
+            return Err(SequenceError::Empty);
+        }
+        if kinds.len() > MAX_SEQUENCE_LEN {
+            return Err(SequenceError::TooLong);
+        }
+
+        let mut sequence = Self {
+            kinds: [MinoKind::I; MAX_SEQUENCE_LEN],
+            len: kinds.len(),
+            position: 0,
+        };
+        sequence.kinds[..kinds.len()].copy_from_slice(kinds);
+        Ok(sequence)
+    }
+
+    /// Parse letters of kinds such as "TIOLJSZ". Whitespaces are ignored.
+    pub fn parse(s: &str) -> Result<Self, SequenceError> {
+        let mut kinds = [MinoKind::I; MAX_SEQUENCE_LEN];
+        let mut len = 0;
+
+        for c in s.chars().filter(|c| !c.is_whitespace()) {
+            let kind = MinoKind::from_char(c).ok_or(SequenceError::UnknownKind(c))?;
+            if len == MAX_SEQUENCE_LEN {
+                return Err(SequenceError::TooLong);
+            }
+            kinds[len] = kind;
+            len += 1;
+        }
+
+        Self::new(&kinds[..len])
+    }
+
+    pub fn kinds(&self) -> &[MinoKind] {
+        &self.kinds[..self.len]
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedSequence {
+    #[serde(with = "crate::big_array")]
+    kinds: [MinoKind; MAX_SEQUENCE_LEN],
+    len: usize,
+    position: usize,
+}
+
+#[cfg(feature = "serde")]
+impl From<Sequence> for SavedSequence {
+    fn from(sequence: Sequence) -> Self {
+        Self {
+            kinds: sequence.kinds,
+            len: sequence.len,
+            position: sequence.position,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl core::convert::TryFrom<SavedSequence> for Sequence {
+    type Error = &'static str;
+
+    fn try_from(saved: SavedSequence) -> Result<Self, Self::Error> {
+        if saved.len > MAX_SEQUENCE_LEN {
+            return Err("sequence over the capacity");
+        }
+
+        let mut sequence =
+            Sequence::new(&saved.kinds[..saved.len]).map_err(|_| "empty sequence")?;
+        if saved.position >= saved.len {
+            return Err("sequence position out of the sequence");
+        }
+
+        sequence.position = saved.position;
+        Ok(sequence)
+    }
+}
+
+impl Randomizer for Sequence {
+    fn next(&mut self, _: &mut dyn RngCore) -> MinoKind {
+        let kind = self.kinds[self.position];
+        self.position = (self.position + 1) % self.len;
+        kind
+    }
+}
+
+pub const CUSTOM_STATE_LEN: usize = 16;
+
+/// Randomizer shared as a static for RandomizerRule::Custom.
+/// The state is kept in CustomRandomizer instead, to be copied with GameState.
+pub trait RandomizerSystem {
+    fn name(&self) -> &'static str;
+    fn next(&self, state: &mut [u8; CUSTOM_STATE_LEN], rng: &mut dyn RngCore) -> MinoKind;
+}
+
+/// A RandomizerSystem with its state.
+#[derive(Copy, Clone)]
+pub struct CustomRandomizer {
+    system: &'static dyn RandomizerSystem,
+    state: [u8; CUSTOM_STATE_LEN],
+}
+
+impl CustomRandomizer {
+    pub fn new(system: &'static dyn RandomizerSystem, state: [u8; CUSTOM_STATE_LEN]) -> Self {
+        Self { system, state }
+    }
+
+    pub fn state(&self) -> &[u8; CUSTOM_STATE_LEN] {
+        &self.state
+    }
+}
+
+impl Randomizer for CustomRandomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind {
+        self.system.next(&mut self.state, rng)
+    }
+}
+
+impl fmt::Debug for CustomRandomizer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(self.system.name())
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+/// Custom systems are equal only to themselves, as RotationRule::Custom.
+impl PartialEq for CustomRandomizer {
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.system, other.system)
+            && self.system.name() == other.system.name()
+            && self.state == other.state
+    }
+}
+
+impl Eq for CustomRandomizer {}
+
+/// Choice of Randomizer in GameConfig.
+/// Game copies it at the start, so the config keeps the initial state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RandomizerRule {
+    Bag(Bag),
+    Random(PureRandom),
+    Nes(NesRandom),
+    Tgm(TgmHistory),
+    Sequence(Sequence),
+    // The system cannot be saved, so GameState with it fails to serialize.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(CustomRandomizer),
+}
+
+impl Default for RandomizerRule {
+    fn default() -> Self {
+        RandomizerRule::Bag(Bag::seven())
+    }
+}
+
+impl Randomizer for RandomizerRule {
+    fn next(&mut self, rng: &mut dyn RngCore) -> MinoKind {
+        match self {
+            RandomizerRule::Bag(r) => r.next(rng),
+            RandomizerRule::Random(r) => r.next(rng),
+            RandomizerRule::Nes(r) => r.next(rng),
+            RandomizerRule::Tgm(r) => r.next(rng),
+            RandomizerRule::Sequence(r) => r.next(rng),
+            RandomizerRule::Custom(r) => r.next(rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::SmallRng;
+    use rand::{RngCore, SeedableRng};
+    use std::prelude::v1::*;
+
+    fn take(mut randomizer: impl Randomizer, seed: u8, n: usize) -> Vec<MinoKind> {
+        let mut rng = SmallRng::from_seed([seed; 16]);
+        (0..n).map(|_| randomizer.next(&mut rng)).collect()
+    }
+
+    fn count(kinds: &[MinoKind], kind: MinoKind) -> usize {
+        kinds.iter().filter(|k| **k == kind).count()
+    }
+
+    #[test]
+    fn test_bag() {
+        for seed in 0..10 {
+            let kinds = take(Bag::seven(), seed, 70);
+            for bag in kinds.chunks(7) {
+                assert!(MinoKind::ALL.iter().all(|k| count(bag, *k) == 1));
+            }
+
+            let kinds = take(Bag::fourteen(), seed, 70);
+            for bag in kinds.chunks(14) {
+                assert!(MinoKind::ALL.iter().all(|k| count(bag, *k) == 2));
+            }
+        }
+
+        // the last of the first bag is shuffled too
+        let lasts = (0..10)
+            .map(|seed| take(Bag::seven(), seed, 7)[6])
+            .collect::<Vec<_>>();
+        assert!(lasts.iter().any(|k| *k != lasts[0]));
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let rules = [
+            RandomizerRule::Bag(Bag::seven()),
+            RandomizerRule::Random(PureRandom),
+            RandomizerRule::Nes(NesRandom::default()),
+            RandomizerRule::Tgm(TgmHistory::tgm2()),
+        ];
+
+        for rule in rules.iter() {
+            assert_eq!(take(*rule, 3, 50), take(*rule, 3, 50));
+            assert_ne!(take(*rule, 3, 50), take(*rule, 4, 50));
+        }
+    }
+
+    #[test]
+    fn test_tgm_first() {
+        for seed in 0..20 {
+            for tgm in [TgmHistory::tgm(), TgmHistory::tgm2()].iter() {
+                let first = take(*tgm, seed, 1)[0];
+                assert!(![MinoKind::S, MinoKind::Z, MinoKind::O].contains(&first));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tgm_history() {
+        use MinoKind::*;
+
+        assert_eq!([Z, Z, Z, Z], TgmHistory::tgm().history);
+        assert_eq!([Z, S, S, Z], TgmHistory::tgm2().history);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let sequence = Sequence::parse("TIO ljsz").unwrap();
+        let kinds = take(sequence, 0, 9);
+        assert_eq!(
+            vec![
+                MinoKind::T,
+                MinoKind::I,
+                MinoKind::O,
+                MinoKind::L,
+                MinoKind::J,
+                MinoKind::S,
+                MinoKind::Z,
+                MinoKind::T,
+                MinoKind::I,
+            ],
+            kinds
+        );
+
+        assert_eq!(Err(SequenceError::Empty), Sequence::parse(" "));
+        assert_eq!(Err(SequenceError::UnknownKind('X')), Sequence::parse("TX"));
+        let long = "T".repeat(MAX_SEQUENCE_LEN + 1);
+        assert_eq!(Err(SequenceError::TooLong), Sequence::parse(&long));
+    }
+
+    // I, O, T, ... in the order of MinoKind
+    struct InOrder;
+
+    impl RandomizerSystem for InOrder {
+        fn name(&self) -> &'static str {
+            "In order"
+        }
+
+        fn next(&self, state: &mut [u8; CUSTOM_STATE_LEN], _: &mut dyn RngCore) -> MinoKind {
+            let kind = MinoKind::from_index(state[0] as usize);
+            state[0] = (state[0] + 1) % 7;
+            kind
+        }
+    }
+
+    #[test]
+    fn test_custom() {
+        static IN_ORDER: InOrder = InOrder;
+
+        let rule = RandomizerRule::Custom(CustomRandomizer::new(&IN_ORDER, [5; CUSTOM_STATE_LEN]));
+        let kinds = take(rule, 0, 9);
+        assert_eq!(&MinoKind::ALL[5..], &kinds[..2]);
+        assert_eq!(&MinoKind::ALL[..], &kinds[2..]);
+
+        let mut randomizer = CustomRandomizer::new(&IN_ORDER, [0; CUSTOM_STATE_LEN]);
+        assert_eq!(
+            RandomizerRule::Custom(randomizer),
+            RandomizerRule::Custom(randomizer)
+        );
+        let copied = randomizer;
+        randomizer.next(&mut SmallRng::from_seed([0; 16]));
+        assert_eq!(1, randomizer.state()[0]);
+        assert_ne!(copied, randomizer);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_save_and_load() {
+        let mut rng = SmallRng::from_seed([0; 16]);
+        let mut bag = Bag::fourteen();
+        let mut tgm = TgmHistory::tgm2();
+        let mut sequence = Sequence::parse("TIO").unwrap();
+        for _ in 0..3 {
+            bag.next(&mut rng);
+            tgm.next(&mut rng);
+            sequence.next(&mut rng);
+        }
+
+        let saved = serde_json::to_value(&bag).unwrap();
+        assert_eq!(bag, serde_json::from_value(saved.clone()).unwrap());
+        for (key, value) in [("copies", 3), ("position", 15)].iter() {
+            let mut broken = saved.clone();
+            broken[*key] = (*value).into();
+            assert!(serde_json::from_value::<Bag>(broken).is_err());
+        }
+        let mut doubled = saved;
+        doubled["kinds"][0] = doubled["kinds"][1].clone();
+        assert!(serde_json::from_value::<Bag>(doubled).is_err());
+
+        let saved = serde_json::to_value(&tgm).unwrap();
+        assert_eq!(tgm, serde_json::from_value(saved.clone()).unwrap());
+        let mut started = serde_json::to_value(&TgmHistory::tgm2()).unwrap();
+        started["history"] = saved["history"].clone();
+        assert!(serde_json::from_value::<TgmHistory>(started).is_err());
+
+        let saved = serde_json::to_value(&sequence).unwrap();
+        assert_eq!(sequence, serde_json::from_value(saved.clone()).unwrap());
+        for (key, value) in [("len", 0), ("len", 65), ("position", 3)].iter() {
+            let mut broken = saved.clone();
+            broken[*key] = (*value).into();
+            assert!(serde_json::from_value::<Sequence>(broken).is_err());
+        }
+    }
+}
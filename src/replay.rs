@@ -0,0 +1,666 @@
+use crate::*;
+use core::fmt;
+
+/// Head of a replay, followed by REPLAY_VERSION.
+pub const REPLAY_MAGIC: [u8; 4] = *b"TRPL";
+pub const REPLAY_VERSION: u8 = 1;
+
+// Events by the code in a replay. Event::TimeGo is counted as frames instead.
+const EVENT_CODES: [Event; 10] = [
+    Event::MoveR,
+    Event::MoveL,
+    Event::MoveDown,
+    Event::Land,
+    Event::RotateR,
+    Event::RotateL,
+    Event::Rotate180,
+    Event::Hold,
+    Event::FreeFall,
+    Event::Nop,
+];
+const END_CODE: u8 = 0xff;
+
+const SCORE_LEN: usize = 15;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReplayError {
+    BufferFull,
+    BadMagic,
+    UnsupportedVersion(u8),
+    Broken,
+    // RotationRule::Custom cannot be saved.
+    CustomRotation,
+    // RandomizerRule::Custom cannot be saved.
+    CustomRandomizer,
+    UnsupportedEvent(Event),
+    ScoreMismatch,
+    FieldMismatch,
+}
+
+/// Records a game in a binary replay.
+///
+/// Start recording just after Game::start and pass every event given to Game::step,
+/// then finish with the game to save the final Score and the field digest.
+/// Randomizers are saved by their parameters, so the one in the config must be fresh.
+pub struct ReplayRecorder<'a> {
+    writer: Writer<'a>,
+    // Event::TimeGo recorded
+    frames: u32,
+    // frames at the last event
+    last_frame: u32,
+}
+
+impl<'a> ReplayRecorder<'a> {
+    pub fn new(
+        buf: &'a mut [u8],
+        seed: [u8; 16],
+        config: &GameConfig,
+    ) -> Result<Self, ReplayError> {
+        let mut writer = Writer { buf, len: 0 };
+        writer.bytes(&REPLAY_MAGIC)?;
+        writer.u8(REPLAY_VERSION)?;
+        writer.bytes(&seed)?;
+        write_config(&mut writer, config)?;
+
+        Ok(Self {
+            writer,
+            frames: 0,
+            last_frame: 0,
+        })
+    }
+
+    pub fn record(&mut self, event: Event) -> Result<(), ReplayError> {
+        if event == Event::TimeGo {
+            self.frames += 1;
+            return Ok(());
+        }
+
+        let code = EVENT_CODES
+            .iter()
+            .position(|e| *e == event)
+            .ok_or(ReplayError::UnsupportedEvent(event))?;
+
+        self.writer.varint((self.frames - self.last_frame) as u64)?;
+        self.writer.u8(code as u8)?;
+        self.last_frame = self.frames;
+        Ok(())
+    }
+
+    /// Record the event and step the game with it.
+    pub fn step<S: EventSink>(
+        &mut self,
+        game: &mut Game<S>,
+        event: Event,
+    ) -> Result<(), ReplayError> {
+        self.record(event)?;
+        game.step(event);
+        Ok(())
+    }
+
+    /// Return the length of the replay in the buffer.
+    pub fn finish<S: EventSink>(mut self, game: &Game<S>) -> Result<usize, ReplayError> {
+        self.writer.varint((self.frames - self.last_frame) as u64)?;
+        self.writer.u8(END_CODE)?;
+
+        for value in score_values(game.score()).iter() {
+            self.writer.varint(*value as u64)?;
+        }
+        self.writer.bytes(&game.field().digest().to_le_bytes())?;
+
+        Ok(self.writer.len)
+    }
+}
+
+/// A replay read from bytes. The whole bytes are checked at parse.
+#[derive(Debug, Copy, Clone)]
+pub struct Replay<'a> {
+    seed: [u8; 16],
+    config: GameConfig,
+    events: &'a [u8],
+    frames: u32,
+    score: Score,
+    digest: u64,
+}
+
+impl<'a> Replay<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, ReplayError> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        if reader.bytes(REPLAY_MAGIC.len())? != REPLAY_MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = reader.u8()?;
+        if version != REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let mut seed = [0; 16];
+        seed.copy_from_slice(reader.bytes(16)?);
+        let config = read_config(&mut reader)?;
+
+        let start = reader.pos;
+        let mut frames = 0u32;
+        loop {
+            frames = frames
+                .checked_add(reader.varint()? as u32)
+                .ok_or(ReplayError::Broken)?;
+            let code = reader.u8()?;
+            if code == END_CODE {
+                break;
+            }
+            if EVENT_CODES.len() <= code as usize {
+                return Err(ReplayError::Broken);
+            }
+        }
+        let events = &bytes[start..reader.pos];
+
+        let mut values = [0; SCORE_LEN];
+        for value in values.iter_mut() {
+            *value = reader.varint()? as usize;
+        }
+
+        let mut digest = [0; 8];
+        digest.copy_from_slice(reader.bytes(8)?);
+
+        Ok(Self {
+            seed,
+            config,
+            events,
+            frames,
+            score: score_from_values(&values),
+            digest: u64::from_le_bytes(digest),
+        })
+    }
+
+    pub fn seed(&self) -> [u8; 16] {
+        self.seed
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// Event::TimeGo in the whole replay.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Score at the end.
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// Field::digest at the end.
+    pub fn digest(&self) -> u64 {
+        self.digest
+    }
+
+    /// Events with the frame, not including Event::TimeGo.
+    pub fn events(&self) -> impl Iterator<Item = (u32, Event)> + 'a {
+        let mut reader = Reader {
+            bytes: self.events,
+            pos: 0,
+        };
+        let mut frame = 0;
+
+        core::iter::from_fn(move || {
+            frame += reader.varint().ok()? as u32;
+            let event = *EVENT_CODES.get(reader.u8().ok()? as usize)?;
+            Some((frame, event))
+        })
+    }
+
+    /// Play a new game by the replay and check the result.
+    pub fn play<S: EventSink>(&self, sink: S) -> Result<Game<S>, ReplayError> {
+        let mut game = Game::new(self.seed, self.config, sink).map_err(|_| ReplayError::Broken)?;
+        game.start();
+
+        let mut frame = 0;
+        for (event_frame, event) in self.events() {
+            while frame < event_frame {
+                game.step(Event::TimeGo);
+                frame += 1;
+            }
+            game.step(event);
+        }
+        while frame < self.frames {
+            game.step(Event::TimeGo);
+            frame += 1;
+        }
+
+        if *game.score() != self.score {
+            return Err(ReplayError::ScoreMismatch);
+        }
+        if game.field().digest() != self.digest {
+            return Err(ReplayError::FieldMismatch);
+        }
+
+        Ok(game)
+    }
+}
+
+/// Text dump of the replay.
+impl<'a> fmt::Display for Replay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "replay v{}", REPLAY_VERSION)?;
+        write!(f, "seed ")?;
+        for byte in self.seed.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "config {:?}", self.config)?;
+
+        for (frame, event) in self.events() {
+            writeln!(f, "{} {:?}", frame, event)?;
+        }
+
+        writeln!(f, "end {}", self.frames)?;
+        writeln!(f, "score {:?}", self.score)?;
+        writeln!(f, "field {:016x}", self.digest)
+    }
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn u8(&mut self, value: u8) -> Result<(), ReplayError> {
+        let byte = self.buf.get_mut(self.len).ok_or(ReplayError::BufferFull)?;
+        *byte = value;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), ReplayError> {
+        bytes.iter().try_for_each(|b| self.u8(*b))
+    }
+
+    // LEB128
+    fn varint(&mut self, mut value: u64) -> Result<(), ReplayError> {
+        while value >= 0x80 {
+            self.u8(value as u8 | 0x80)?;
+            value >>= 7;
+        }
+        self.u8(value as u8)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        let byte = *self.bytes.get(self.pos).ok_or(ReplayError::Broken)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], ReplayError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or(ReplayError::Broken)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64, ReplayError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ReplayError::Broken)
+    }
+
+    fn bool(&mut self) -> Result<bool, ReplayError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ReplayError::Broken),
+        }
+    }
+
+    fn kind(&mut self) -> Result<MinoKind, ReplayError> {
+        let index = self.u8()? as usize;
+        if index < MinoKind::ALL.len() {
+            Ok(MinoKind::from_index(index))
+        } else {
+            Err(ReplayError::Broken)
+        }
+    }
+}
+
+fn write_config(writer: &mut Writer, config: &GameConfig) -> Result<(), ReplayError> {
+    writer.u8(config.field_w as u8)?;
+    writer.u8(config.display_field_h as u8)?;
+    writer.u8(config.field_h as u8)?;
+    writer.u8(config.mino_first_position.0 as u8)?;
+    writer.u8(config.mino_first_position.1 as u8)?;
+    writer.u8(config.preview as u8)?;
+
+    match config.randomizer {
+        RandomizerRule::Bag(bag) => writer.bytes(&[0, bag.copies() as u8])?,
+        RandomizerRule::Random(_) => writer.u8(1)?,
+        RandomizerRule::Nes(_) => writer.u8(2)?,
+        RandomizerRule::Tgm(tgm) => writer.bytes(&[3, tgm.rolls()])?,
+        RandomizerRule::Sequence(sequence) => {
+            writer.bytes(&[4, sequence.kinds().len() as u8])?;
+            for kind in sequence.kinds().iter() {
+                writer.u8(kind.index() as u8)?;
+            }
+        }
+        RandomizerRule::Custom(_) => return Err(ReplayError::CustomRandomizer),
+    }
+
+    writer.u8(match config.rotation {
+        RotationRule::Srs => 0,
+        RotationRule::SrsPlus => 1,
+        RotationRule::Ars => 2,
+        RotationRule::Nrs => 3,
+        RotationRule::NoKick => 4,
+        RotationRule::Custom(_) => return Err(ReplayError::CustomRotation),
+    })?;
+
+    let lock_delay = config.lock_delay;
+    writer.u8(lock_delay.frames)?;
+    match lock_delay.reset {
+        LockReset::Move(limit) => writer.bytes(&[0, limit])?,
+        LockReset::Step => writer.u8(1)?,
+        LockReset::Infinity => writer.u8(2)?,
+    }
+    writer.u8(lock_delay.move_down_locks as u8)?;
+    writer.u8(config.line_clear_delay)?;
+
+    let spawn = config.spawn;
+    writer.u8(spawn.block_out as u8)?;
+    writer.u8(spawn.lock_out as u8)?;
+    writer.u8(spawn.drop_on_spawn as u8)?;
+
+    let (tag, value) = match config.mode {
+        GameMode::Endless => (0, 0),
+        GameMode::Marathon { lines } => (1, lines as u64),
+        GameMode::Sprint { lines } => (2, lines as u64),
+        GameMode::Ultra { frames } => (3, frames as u64),
+        GameMode::Zen => (4, 0),
+    };
+    writer.u8(tag)?;
+    writer.varint(value)
+}
+
+fn read_config(reader: &mut Reader) -> Result<GameConfig, ReplayError> {
+    let field_w = reader.u8()? as usize;
+    let display_field_h = reader.u8()? as usize;
+    let field_h = reader.u8()? as usize;
+    let mino_first_position = (reader.u8()? as i8, reader.u8()? as i8);
+    let preview = reader.u8()? as usize;
+
+    let randomizer = match reader.u8()? {
+        0 => match reader.u8()? {
+            copies @ 1..=2 => RandomizerRule::Bag(Bag::new(copies as usize)),
+            _ => return Err(ReplayError::Broken),
+        },
+        1 => RandomizerRule::Random(PureRandom),
+        2 => RandomizerRule::Nes(NesRandom::default()),
+        3 => RandomizerRule::Tgm(TgmHistory::new(reader.u8()?)),
+        4 => {
+            let mut kinds = [MinoKind::I; MAX_SEQUENCE_LEN];
+            let len = reader.u8()? as usize;
+            for kind in kinds.iter_mut().take(len) {
+                *kind = reader.kind()?;
+            }
+            let kinds = kinds.get(..len).ok_or(ReplayError::Broken)?;
+            RandomizerRule::Sequence(Sequence::new(kinds).map_err(|_| ReplayError::Broken)?)
+        }
+        _ => return Err(ReplayError::Broken),
+    };
+
+    let rotation = match reader.u8()? {
+        0 => RotationRule::Srs,
+        1 => RotationRule::SrsPlus,
+        2 => RotationRule::Ars,
+        3 => RotationRule::Nrs,
+        4 => RotationRule::NoKick,
+        _ => return Err(ReplayError::Broken),
+    };
+
+    let frames = reader.u8()?;
+    let reset = match reader.u8()? {
+        0 => LockReset::Move(reader.u8()?),
+        1 => LockReset::Step,
+        2 => LockReset::Infinity,
+        _ => return Err(ReplayError::Broken),
+    };
+    let lock_delay = LockDelay {
+        frames,
+        reset,
+        move_down_locks: reader.bool()?,
+    };
+    let line_clear_delay = reader.u8()?;
+
+    let spawn = SpawnRule {
+        block_out: reader.bool()?,
+        lock_out: reader.bool()?,
+        drop_on_spawn: reader.bool()?,
+    };
+
+    let tag = reader.u8()?;
+    let value = reader.varint()?;
+    let mode = match tag {
+        0 => GameMode::Endless,
+        1 => GameMode::Marathon {
+            lines: value as usize,
+        },
+        2 => GameMode::Sprint {
+            lines: value as usize,
+        },
+        3 => GameMode::Ultra {
+            frames: value as u32,
+        },
+        4 => GameMode::Zen,
+        _ => return Err(ReplayError::Broken),
+    };
+
+    let config = GameConfig {
+        field_w,
+        display_field_h,
+        field_h,
+        mino_first_position,
+        preview,
+        randomizer,
+        rotation,
+        lock_delay,
+        line_clear_delay,
+        spawn,
+        mode,
+    };
+    config.validate().map_err(|_| ReplayError::Broken)?;
+    Ok(config)
+}
+
+fn score_values(score: &Score) -> [usize; SCORE_LEN] {
+    [
+        score.level,
+        score.point,
+        score.combo,
+        score.back_to_back,
+        score.perfect_clear,
+        score.deleted_line,
+        score.t_spin_zero,
+        score.t_spin1,
+        score.t_spin2,
+        score.t_spin3,
+        score.t_spin_mini_zero,
+        score.t_spin_mini1,
+        score.t_spin_mini2,
+        score.tetris,
+        score.finesse_fault,
+    ]
+}
+
+fn score_from_values(values: &[usize; SCORE_LEN]) -> Score {
+    Score {
+        level: values[0],
+        point: values[1],
+        combo: values[2],
+        back_to_back: values[3],
+        perfect_clear: values[4],
+        deleted_line: values[5],
+        t_spin_zero: values[6],
+        t_spin1: values[7],
+        t_spin2: values[8],
+        t_spin3: values[9],
+        t_spin_mini_zero: values[10],
+        t_spin_mini1: values[11],
+        t_spin_mini2: values[12],
+        tetris: values[13],
+        finesse_fault: values[14],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::prelude::v1::*;
+
+    const SEED: [u8; 16] = [3; 16];
+
+    fn record(config: &GameConfig, buf: &mut [u8]) -> (usize, Score) {
+        let mut game = Game::new(SEED, *config, |_| {}).unwrap();
+        game.start();
+
+        let mut recorder = ReplayRecorder::new(buf, SEED, config).unwrap();
+        let inputs = [
+            Event::MoveL,
+            Event::TimeGo,
+            Event::RotateR,
+            Event::Land,
+            Event::TimeGo,
+            Event::MoveR,
+            Event::TimeGo,
+            Event::Hold,
+            Event::MoveR,
+            Event::MoveR,
+            Event::Land,
+        ];
+        for event in inputs.iter().cycle().take(200) {
+            recorder.step(&mut game, *event).unwrap();
+        }
+        for _ in 0..30 {
+            recorder.step(&mut game, Event::TimeGo).unwrap();
+        }
+
+        (recorder.finish(&game).unwrap(), *game.score())
+    }
+
+    #[test]
+    fn test_replay() {
+        let config = GameConfig {
+            randomizer: RandomizerRule::Sequence(Sequence::parse("TSZLJIO").unwrap()),
+            rotation: RotationRule::SrsPlus,
+            mode: GameMode::Sprint { lines: 40 },
+            ..Default::default()
+        };
+        let mut buf = [0; 1024];
+        let (len, score) = record(&config, &mut buf);
+
+        let replay = Replay::parse(&buf[..len]).unwrap();
+        assert_eq!(&config, replay.config());
+        assert_eq!(&score, replay.score());
+        assert_eq!(18 * 3 + 1 + 30, replay.frames());
+
+        let game = replay.play(|_| {}).unwrap();
+        assert_eq!(&score, game.score());
+
+        let dump = replay.to_string();
+        assert!(dump.starts_with("replay v1\nseed 0303"));
+        assert!(dump.contains("\n0 MoveL\n1 RotateR\n1 Land\n"));
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let config = GameConfig::default();
+        let mut buf = [0; 1024];
+        let (len, _) = record(&config, &mut buf);
+
+        // the last varint of Score and the digest
+        let mut score = buf;
+        score[len - 9] = 99;
+        assert_eq!(
+            Err(ReplayError::ScoreMismatch),
+            Replay::parse(&score[..len])
+                .unwrap()
+                .play(|_| {})
+                .map(|_| ())
+        );
+        let mut field = buf;
+        field[len - 1] ^= 1;
+        assert_eq!(
+            Err(ReplayError::FieldMismatch),
+            Replay::parse(&field[..len])
+                .unwrap()
+                .play(|_| {})
+                .map(|_| ())
+        );
+
+        assert_eq!(
+            Err(ReplayError::Broken),
+            Replay::parse(&buf[..len - 1]).map(|_| ())
+        );
+        assert_eq!(
+            Err(ReplayError::BadMagic),
+            Replay::parse(b"TRPX").map(|_| ())
+        );
+
+        let mut version = buf;
+        version[4] = 2;
+        assert_eq!(
+            Err(ReplayError::UnsupportedVersion(2)),
+            Replay::parse(&version[..len]).map(|_| ())
+        );
+
+        let mut small = [0; 32];
+        assert_eq!(
+            Err(ReplayError::BufferFull),
+            ReplayRecorder::new(&mut small, SEED, &config).map(|_| ())
+        );
+
+        static CUSTOM: NoKick = NoKick;
+        let custom = GameConfig {
+            rotation: RotationRule::Custom(&CUSTOM),
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(ReplayError::CustomRotation),
+            ReplayRecorder::new(&mut buf, SEED, &custom).map(|_| ())
+        );
+
+        struct Only;
+        impl RandomizerSystem for Only {
+            fn name(&self) -> &'static str {
+                "Only T"
+            }
+
+            fn next(&self, _: &mut [u8; CUSTOM_STATE_LEN], _: &mut dyn rand::RngCore) -> MinoKind {
+                MinoKind::T
+            }
+        }
+
+        static ONLY: Only = Only;
+        let custom = GameConfig {
+            randomizer: RandomizerRule::Custom(CustomRandomizer::new(&ONLY, [0; CUSTOM_STATE_LEN])),
+            ..Default::default()
+        };
+        assert_eq!(
+            Err(ReplayError::CustomRandomizer),
+            ReplayRecorder::new(&mut buf, SEED, &custom).map(|_| ())
+        );
+    }
+}
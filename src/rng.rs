@@ -0,0 +1,70 @@
+use rand::{Error, RngCore, SeedableRng};
+
+const MULTIPLIER: u128 = 0x2360_ED05_1FC6_5DA4_4385_DF64_9FCC_F645;
+
+/// PCG with a 128 bit MCG and the XSL RR output, the same as SmallRng on 64 bit platforms.
+/// Owned by the crate so that the state can be copied and compared.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameRng {
+    state: u128,
+}
+
+impl SeedableRng for GameRng {
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: [u8; 16]) -> Self {
+        // the low bit must be 1
+        Self {
+            state: u128::from_le_bytes(seed) | 1,
+        }
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.next_u64() as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_mul(MULTIPLIER);
+
+        let rotation = (self.state >> 122) as u32;
+        let xsl = ((self.state >> 64) as u64) ^ (self.state as u64);
+        xsl.rotate_right(rotation)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::prelude::SmallRng;
+    use rand::{RngCore, SeedableRng};
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_same_as_small_rng() {
+        let mut rng = GameRng::from_seed([7; 16]);
+        let mut small = SmallRng::from_seed([7; 16]);
+        for _ in 0..100 {
+            assert_eq!(small.next_u64(), rng.next_u64());
+        }
+
+        let (mut a, mut b) = ([0; 13], [0; 13]);
+        rng.fill_bytes(&mut a);
+        small.fill_bytes(&mut b);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::*;
+use core::fmt;
+
+/// Rules of rotation, swappable per game by GameConfig::rotation.
+///
+/// Minos are always placed as SRS states,
+/// so a system with other placements shifts them by state_offset.
+pub trait RotationSystem {
+    fn name(&self) -> &'static str;
+
+    /// Rotation state of a new mino.
+    fn spawn_rotation(&self, kind: MinoKind) -> Rotation {
+        Rotation::State0
+    }
+
+    /// Shift from the SRS placement of the state.
+    fn state_offset(&self, kind: MinoKind, rotation: Rotation) -> (i8, i8) {
+        (0, 0)
+    }
+
+    /// Offsets to try in order after the shift by state_offset.
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)];
+
+    /// Called when the rotation without kicks fails.
+    /// Return false to give up the remaining kicks.
+    fn can_kick(
+        &self,
+        kind: MinoKind,
+        to: Rotation,
+        field: &Field,
+        pos: (i8, i8),
+        cells: &[(i8, i8)],
+    ) -> bool {
+        true
+    }
+}
+
+const NO_KICKS: [(i8, i8); 1] = [(0, 0)];
+
+/// Put the rotated mino at the first kick that fits in the field.
+/// The mino must be at the position before rotation.
+/// Return the index of the kick.
+pub fn kick<S: RotationSystem + ?Sized, M: MinoFn>(
+    system: &S,
+    field: &Field,
+    from: Rotation,
+    rotated: &mut M,
+) -> Option<usize> {
+    let kind = rotated.kind();
+    let to = rotated.rotation();
+
+    let (from_x, from_y) = system.state_offset(kind, from);
+    let (to_x, to_y) = system.state_offset(kind, to);
+    let (x, y) = rotated.pos();
+    let (x, y) = (x + to_x - from_x, y + to_y - from_y);
+
+    for (i, (offset_x, offset_y)) in system.kicks(kind, from, to).iter().enumerate() {
+        rotated.absolute((x + offset_x, y + offset_y));
+        if !rotated.test_with_field(field) {
+            return Some(i);
+        }
+
+        if i == 0 && !system.can_kick(kind, to, field, (x, y), M::cells()) {
+            break;
+        }
+    }
+
+    None
+}
+
+/// Guideline Super Rotation System.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Srs;
+
+impl RotationSystem for Srs {
+    fn name(&self) -> &'static str {
+        "SRS"
+    }
+
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+        srs_offset(kind, from, to)
+    }
+}
+
+/// SRS of TETR.IO. Kicks of MinoI are symmetric, and 180 kicks are the same as Srs.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct SrsPlus;
+
+impl RotationSystem for SrsPlus {
+    fn name(&self) -> &'static str {
+        "SRS+"
+    }
+
+    #[rustfmt::skip]
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+        use Rotation::*;
+
+        if kind != MinoKind::I {
+            return srs_offset(kind, from, to);
+        }
+
+        match (from, to) {
+            (State0, StateR) => &[(0, 0), (1, 0), (-2, 0), (-2, 1), (1, -2)],
+            (StateR, State0) => &[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+            (StateR, State2) => &[(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)],
+            (State2, StateR) => &[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+            (State2, StateL) => &[(0, 0), (2, 0), (-1, 0), (2, -1), (-1, 2)],
+            (StateL, State2) => &[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+            (StateL, State0) => &[(0, 0), (1, 0), (-2, 0), (1, 2), (-2, -1)],
+            (State0, StateL) => &[(0, 0), (-1, 0), (2, 0), (2, 1), (-1, -2)],
+            _ => srs_offset(kind, from, to),
+        }
+    }
+}
+
+/// Arika Rotation System of TGM.
+///
+/// Minos spawn flat side up and keep the bottom in a 3x3 box.
+/// MinoI never kicks, and others kick a cell right, then left,
+/// unless the center column rule stops MinoJ, MinoL and MinoT.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Ars;
+
+impl RotationSystem for Ars {
+    fn name(&self) -> &'static str {
+        "ARS"
+    }
+
+    fn spawn_rotation(&self, kind: MinoKind) -> Rotation {
+        match kind {
+            MinoKind::I | MinoKind::O => Rotation::State0,
+            _ => Rotation::State2,
+        }
+    }
+
+    fn state_offset(&self, kind: MinoKind, rotation: Rotation) -> (i8, i8) {
+        match (kind, rotation) {
+            (MinoKind::O, _) => (0, 0),
+            (MinoKind::I, Rotation::State2) => (0, -1),
+            (MinoKind::I, Rotation::StateL) => (1, 0),
+            (MinoKind::S, Rotation::StateR) => (-1, 0),
+            (MinoKind::Z, Rotation::StateL) => (1, 0),
+            (MinoKind::I, _) => (0, 0),
+            (_, Rotation::State0) => (0, 1),
+            _ => (0, 0),
+        }
+    }
+
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+        match kind {
+            MinoKind::I => &NO_KICKS,
+            _ => &[(0, 0), (1, 0), (-1, 0)],
+        }
+    }
+
+    /// Center column rule.
+    /// Scanning the 3x3 box from the top left, no kicks if the first cell
+    /// hitting a block is in the center column.
+    fn can_kick(
+        &self,
+        kind: MinoKind,
+        to: Rotation,
+        field: &Field,
+        (x, y): (i8, i8),
+        cells: &[(i8, i8)],
+    ) -> bool {
+        match kind {
+            MinoKind::J | MinoKind::L | MinoKind::T => {}
+            _ => return true,
+        }
+
+        // the position is the box center shifted by state_offset
+        let (shift_x, shift_y) = self.state_offset(kind, to);
+        for box_y in -1..=1 {
+            for box_x in -1..=1 {
+                let cell = (box_x - shift_x, box_y - shift_y);
+                if cells.contains(&cell) && field.test(x + cell.0, y + cell.1) {
+                    return box_x != 0;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Nintendo Rotation System of NES Tetris.
+///
+/// Minos rotate around a fixed center without kicks.
+/// MinoI, MinoS and MinoZ have only two states, leaning right when standing.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct Nrs;
+
+impl RotationSystem for Nrs {
+    fn name(&self) -> &'static str {
+        "NRS"
+    }
+
+    fn spawn_rotation(&self, kind: MinoKind) -> Rotation {
+        match kind {
+            MinoKind::I | MinoKind::O => Rotation::State0,
+            _ => Rotation::State2,
+        }
+    }
+
+    fn state_offset(&self, kind: MinoKind, rotation: Rotation) -> (i8, i8) {
+        match (kind, rotation) {
+            (MinoKind::I, Rotation::State0) => (-1, 0),
+            (MinoKind::I, Rotation::StateR) => (-1, -1),
+            (MinoKind::I, Rotation::State2) => (-1, -1),
+            (MinoKind::I, Rotation::StateL) => (0, -1),
+            (MinoKind::S, Rotation::State0) | (MinoKind::Z, Rotation::State0) => (0, 1),
+            (MinoKind::S, Rotation::StateL) | (MinoKind::Z, Rotation::StateL) => (1, 0),
+            _ => (0, 0),
+        }
+    }
+
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+        &NO_KICKS
+    }
+}
+
+/// SRS without kicks, for sandbox.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct NoKick;
+
+impl RotationSystem for NoKick {
+    fn name(&self) -> &'static str {
+        "No kick"
+    }
+
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+        &NO_KICKS
+    }
+}
+
+/// Choice of RotationSystem in GameConfig.
+#[derive(Copy, Clone)]
+pub enum RotationRule {
+    Srs,
+    SrsPlus,
+    Ars,
+    Nrs,
+    NoKick,
+    Custom(&'static dyn RotationSystem),
+}
+
+impl Default for RotationRule {
+    fn default() -> Self {
+        RotationRule::Srs
+    }
+}
+
+impl RotationRule {
+    pub fn system(&self) -> &dyn RotationSystem {
+        match self {
+            RotationRule::Srs => &Srs,
+            RotationRule::SrsPlus => &SrsPlus,
+            RotationRule::Ars => &Ars,
+            RotationRule::Nrs => &Nrs,
+            RotationRule::NoKick => &NoKick,
+            RotationRule::Custom(system) => *system,
+        }
+    }
+}
+
+impl fmt::Debug for RotationRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Custom systems are equal only to themselves.
+impl PartialEq for RotationRule {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Zero sized systems can share an address, so the vtable is compared too.
+            (RotationRule::Custom(a), RotationRule::Custom(b)) => {
+                core::ptr::eq(*a, *b) && a.name() == b.name()
+            }
+            (RotationRule::Custom(_), _) | (_, RotationRule::Custom(_)) => false,
+            _ => self.name() == other.name(),
+        }
+    }
+}
+
+impl Eq for RotationRule {}
+
+impl RotationSystem for RotationRule {
+    fn name(&self) -> &'static str {
+        self.system().name()
+    }
+
+    fn spawn_rotation(&self, kind: MinoKind) -> Rotation {
+        self.system().spawn_rotation(kind)
+    }
+
+    fn state_offset(&self, kind: MinoKind, rotation: Rotation) -> (i8, i8) {
+        self.system().state_offset(kind, rotation)
+    }
+
+    fn kicks(&self, kind: MinoKind, from: Rotation, to: Rotation) -> &'static [(i8, i8)] {
+        self.system().kicks(kind, from, to)
+    }
+
+    fn can_kick(
+        &self,
+        kind: MinoKind,
+        to: Rotation,
+        field: &Field,
+        pos: (i8, i8),
+        cells: &[(i8, i8)],
+    ) -> bool {
+        self.system().can_kick(kind, to, field, pos, cells)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::prelude::v1::*;
+
+    #[test]
+    fn test_srs_plus_symmetric() {
+        use Rotation::*;
+
+        let mirrors = [
+            ((State0, StateR), (State0, StateL)),
+            ((StateR, State0), (StateL, State0)),
+            ((StateR, State2), (StateL, State2)),
+            ((State2, StateR), (State2, StateL)),
+        ];
+
+        for ((from, to), (mirror_from, mirror_to)) in mirrors.iter() {
+            let kicks = SrsPlus.kicks(MinoKind::I, *from, *to);
+            let mirrored = SrsPlus
+                .kicks(MinoKind::I, *mirror_from, *mirror_to)
+                .iter()
+                .map(|(x, y)| (-x, *y))
+                .collect::<Vec<_>>();
+            assert_eq!(kicks, &mirrored[..]);
+        }
+
+        let half_turns = [
+            (State0, State2),
+            (State2, State0),
+            (StateR, StateL),
+            (StateL, StateR),
+        ];
+
+        for (from, to) in half_turns.iter() {
+            let kicks = SrsPlus.kicks(MinoKind::I, *from, *to);
+            assert_eq!(Srs.kicks(MinoKind::I, *from, *to), kicks);
+            assert!(kicks.len() > 1);
+        }
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        static CUSTOM: NoKick = NoKick;
+        static OTHER: Srs = Srs;
+
+        let rule = RotationRule::Custom(&CUSTOM);
+        assert_eq!(rule, RotationRule::Custom(&CUSTOM));
+        assert_ne!(rule, RotationRule::Custom(&OTHER));
+        assert_ne!(rule, RotationRule::NoKick);
+        assert_eq!("No kick", rule.name());
+    }
+}